@@ -0,0 +1,86 @@
+use async_trait::async_trait;
+use reqwest::Client;
+
+use crate::bundle::relay::{BundleRelay, post_json_rpc};
+use crate::bundle::types::{
+    BundleError, BundleReceipt, BundleRequest, CallBundleParams, CallBundleResult, CallBundleTx,
+    EthSendBundleParams, EthSendBundleResult,
+};
+
+/// Any builder implementing the BEP-322 `eth_sendBundle` API
+pub struct Bep322Relay {
+    client: Client,
+    url: String,
+    auth: Option<String>,
+}
+
+impl Bep322Relay {
+    pub fn new(url: &str, auth: Option<String>) -> Self {
+        Self {
+            client: Client::new(),
+            url: url.to_string(),
+            auth,
+        }
+    }
+
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.client = client;
+        self
+    }
+}
+
+#[async_trait]
+impl BundleRelay for Bep322Relay {
+    fn name(&self) -> &str {
+        "bep322"
+    }
+
+    async fn send_bundle(&self, bundle: &BundleRequest) -> Result<BundleReceipt, BundleError> {
+        // BEP-322 has no "target block", only an upper bound
+        let params = EthSendBundleParams {
+            txs: bundle.txs.clone(),
+            max_block_number: Some(bundle.last_block()),
+            reverting_tx_hashes: bundle.reverting_tx_hashes.clone(),
+            ..Default::default()
+        };
+
+        let result: EthSendBundleResult = post_json_rpc(
+            &self.client,
+            self.name(),
+            &self.url,
+            self.auth.as_deref(),
+            "eth_sendBundle",
+            [params],
+        )
+        .await?;
+
+        Ok(BundleReceipt {
+            relay: self.name().to_string(),
+            bundle_hash: Some(result.into_hash()),
+            block_number: bundle.block_number,
+        })
+    }
+
+    fn supports_call_bundle(&self) -> bool {
+        true
+    }
+
+    async fn call_bundle(&self, bundle: &BundleRequest) -> Result<Vec<CallBundleTx>, BundleError> {
+        let params = CallBundleParams {
+            txs: bundle.txs.clone(),
+            block_number: format!("0x{:x}", bundle.block_number),
+            state_block_number: "latest".to_string(),
+        };
+
+        let result: CallBundleResult = post_json_rpc(
+            &self.client,
+            self.name(),
+            &self.url,
+            self.auth.as_deref(),
+            "eth_callBundle",
+            [params],
+        )
+        .await?;
+        Ok(result.results)
+    }
+}
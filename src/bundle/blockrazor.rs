@@ -0,0 +1,63 @@
+use async_trait::async_trait;
+use reqwest::Client;
+
+use crate::bundle::relay::{BundleRelay, post_json_rpc};
+use crate::bundle::types::{BundleError, BundleReceipt, BundleRequest, EthSendBundleParams, EthSendBundleResult};
+
+pub const BLOCKRAZOR_URL: &str = "https://bsc.blockrazor.xyz";
+
+/// BlockRazor BSC builder (`eth_sendBundle` with an auth header)
+pub struct BlockRazorRelay {
+    client: Client,
+    url: String,
+    auth: String,
+}
+
+impl BlockRazorRelay {
+    pub fn new(auth: &str, url: Option<String>) -> Self {
+        Self {
+            client: Client::new(),
+            url: url.unwrap_or_else(|| BLOCKRAZOR_URL.to_string()),
+            auth: auth.to_string(),
+        }
+    }
+
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.client = client;
+        self
+    }
+}
+
+#[async_trait]
+impl BundleRelay for BlockRazorRelay {
+    fn name(&self) -> &str {
+        "blockrazor"
+    }
+
+    async fn send_bundle(&self, bundle: &BundleRequest) -> Result<BundleReceipt, BundleError> {
+        // BlockRazor accepts a window via blockNumber..maxBlockNumber
+        let params = EthSendBundleParams {
+            txs: bundle.txs.clone(),
+            block_number: Some(bundle.block_number),
+            max_block_number: Some(bundle.last_block()),
+            reverting_tx_hashes: bundle.reverting_tx_hashes.clone(),
+            ..Default::default()
+        };
+
+        let result: EthSendBundleResult = post_json_rpc(
+            &self.client,
+            self.name(),
+            &self.url,
+            Some(&self.auth),
+            "eth_sendBundle",
+            [params],
+        )
+        .await?;
+
+        Ok(BundleReceipt {
+            relay: self.name().to_string(),
+            bundle_hash: Some(result.into_hash()),
+            block_number: bundle.block_number,
+        })
+    }
+}
@@ -0,0 +1,69 @@
+use async_trait::async_trait;
+use reqwest::Client;
+
+use crate::bundle::relay::{BundleRelay, post_json_rpc};
+use crate::bundle::types::{BlxrBundleParams, BlxrBundleResult, BundleError, BundleReceipt, BundleRequest};
+
+pub const BLOXROUTE_URL: &str = "https://api.blxrbdn.com";
+
+/// bloXroute Cloud API, `blxr_submit_bundle`
+pub struct BloxrouteRelay {
+    client: Client,
+    url: String,
+    auth: String,
+}
+
+impl BloxrouteRelay {
+    pub fn new(auth: &str) -> Self {
+        Self {
+            client: Client::new(),
+            url: BLOXROUTE_URL.to_string(),
+            auth: auth.to_string(),
+        }
+    }
+
+    /// Points the relay at another endpoint (e.g. a regional gateway or a local mock)
+    pub fn with_url(mut self, url: &str) -> Self {
+        self.url = url.to_string();
+        self
+    }
+
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.client = client;
+        self
+    }
+}
+
+#[async_trait]
+impl BundleRelay for BloxrouteRelay {
+    fn name(&self) -> &str {
+        "bloxroute"
+    }
+
+    async fn send_bundle(&self, bundle: &BundleRequest) -> Result<BundleReceipt, BundleError> {
+        // bloXroute wants the block as a hex string and the builders as a name map
+        let params = BlxrBundleParams {
+            transaction: bundle.txs.clone(),
+            blockchain_network: "BSC-Mainnet".to_string(),
+            block_number: format!("0x{:x}", bundle.block_number),
+            reverting_hashes: bundle.reverting_tx_hashes.clone(),
+            mev_builders: bundle.builders.to_mev_builders(),
+        };
+
+        let result: BlxrBundleResult = post_json_rpc(
+            &self.client,
+            self.name(),
+            &self.url,
+            Some(&self.auth),
+            "blxr_submit_bundle",
+            params,
+        )
+        .await?;
+
+        Ok(BundleReceipt {
+            relay: self.name().to_string(),
+            bundle_hash: Some(result.bundle_hash),
+            block_number: bundle.block_number,
+        })
+    }
+}
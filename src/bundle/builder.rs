@@ -0,0 +1,254 @@
+use anyhow::{Context, Result};
+use ethers::prelude::*;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use std::{collections::HashMap, env, str::FromStr, sync::Arc};
+
+use crate::bundle::{BundleRequest, BundleTracker};
+use crate::wallet::WalletSigner;
+
+/// Gas limit used when a tx can't be estimated on its own
+/// (e.g. a buy that depends on the create earlier in the bundle)
+pub const DEFAULT_BUNDLE_GAS_LIMIT: u64 = 500_000;
+
+/// An unsigned tx and the wallet that will sign it
+#[derive(Debug, Clone)]
+pub struct BundleTx {
+    pub tx: TypedTransaction,
+    pub signer: WalletSigner,
+    pub can_revert: bool, // listed in `revertingTxHashes`, the bundle still lands if it reverts
+}
+
+/// A signed bundle plus what it was built against, so it can tell when it went stale
+#[derive(Debug, Clone)]
+pub struct SignedBundle {
+    pub txs: Vec<String>,                   // hex strings of signed txs, in bundle order
+    pub start_nonces: Vec<(Address, U256)>, // first nonce used per sender
+    pub gas_price: U256,
+    pub reverting_tx_hashes: Vec<H256>, // hashes of the txs marked `can_revert`
+}
+
+impl SignedBundle {
+    /// Relay request for `block_number`, carrying the allowed-revert hashes
+    pub fn request(&self, block_number: u64) -> BundleRequest {
+        let mut request = BundleRequest::new(self.txs.clone(), block_number);
+        request.reverting_tx_hashes = self.reverting_tx_hashes.clone();
+        request
+    }
+
+    /// Tracker that doesn't count allowed-revert txs against inclusion
+    pub fn tracker(&self, provider: Arc<Provider<Http>>, target_block: u64) -> Result<BundleTracker> {
+        Ok(BundleTracker::from_signed_txs(provider, &self.txs, target_block)?
+            .allow_revert(self.reverting_tx_hashes.clone()))
+    }
+
+    /// A bundle is stale once any sender's nonce moved or the network gas price rose above ours
+    pub async fn is_stale(&self, provider: &Provider<Http>) -> Result<bool> {
+        for (sender, nonce) in &self.start_nonces {
+            if provider.get_transaction_count(*sender, Some(BlockNumber::Pending.into())).await? != *nonce {
+                return Ok(true);
+            }
+        }
+        Ok(provider.get_gas_price().await? > self.gas_price)
+    }
+}
+
+/// Where the builder tip goes in the bundle
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TipPlacement {
+    First,
+    Last,
+}
+
+impl FromStr for TipPlacement {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "first" => Ok(TipPlacement::First),
+            "last" => Ok(TipPlacement::Last),
+            other => anyhow::bail!("Unknown tip placement: {}", other),
+        }
+    }
+}
+
+/// How much the builder tip pays
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TipAmount {
+    /// A fixed amount in wei
+    Fixed(U256),
+    /// Wei per unit of gas limit of the rest of the bundle. Limits are estimates, and a tx that
+    /// can't be estimated counts as the full fallback limit (500k by default), so this is an
+    /// upper bound on what the bundle actually burns, not the gas it used.
+    PerGas(U256),
+}
+
+/// A plain BNB transfer to the builder, signed by `signer`
+#[derive(Debug, Clone)]
+pub struct BuilderTip {
+    pub to: Address,
+    pub signer: WalletSigner,
+    pub amount: TipAmount,
+    pub placement: TipPlacement,
+}
+
+impl BuilderTip {
+    /// Reads `BUILDER_TIP_ADDRESS` plus `BUILDER_TIP_BNB` or `BUILDER_TIP_WEI_PER_GAS`,
+    /// and `BUILDER_TIP_PLACEMENT` (defaults to last). `None` when no tip address is set or it's empty.
+    pub fn from_env(signer: impl Into<WalletSigner>) -> Result<Option<Self>> {
+        dotenvy::dotenv().ok();
+        let to = env::var("BUILDER_TIP_ADDRESS").unwrap_or_default();
+        if to.trim().is_empty() {
+            return Ok(None);
+        }
+        let amount = match (env::var("BUILDER_TIP_BNB"), env::var("BUILDER_TIP_WEI_PER_GAS")) {
+            (Ok(bnb), _) => TipAmount::Fixed(ethers::utils::parse_ether(bnb.trim())?),
+            (Err(_), Ok(per_gas)) => TipAmount::PerGas(U256::from_dec_str(per_gas.trim())?),
+            _ => anyhow::bail!("BUILDER_TIP_ADDRESS set without BUILDER_TIP_BNB or BUILDER_TIP_WEI_PER_GAS"),
+        };
+        let placement = env::var("BUILDER_TIP_PLACEMENT")
+            .unwrap_or_else(|_| "last".to_string())
+            .parse()?;
+
+        Ok(Some(Self {
+            to: to.trim().parse().context("Invalid BUILDER_TIP_ADDRESS")?,
+            signer: signer.into(),
+            amount,
+            placement,
+        }))
+    }
+}
+
+/// Collects unsigned txs and turns them into a signed bundle with fresh nonces and gas
+pub struct BundleBuilder {
+    provider: Arc<Provider<Http>>,
+    txs: Vec<BundleTx>,
+    tip: Option<BuilderTip>,
+    fallback_gas_limit: U256,
+}
+
+impl BundleBuilder {
+    pub fn new(provider: Arc<Provider<Http>>) -> Self {
+        Self {
+            provider,
+            txs: Vec::new(),
+            tip: None,
+            fallback_gas_limit: U256::from(DEFAULT_BUNDLE_GAS_LIMIT),
+        }
+    }
+
+    /// Appends a tx, executed after everything pushed before it
+    pub fn push(&mut self, tx: TypedTransaction, signer: impl Into<WalletSigner>) -> &mut Self {
+        self.txs.push(BundleTx { tx, signer: signer.into(), can_revert: false });
+        self
+    }
+
+    /// Appends a tx the bundle may land without (e.g. an optional extra buy)
+    pub fn push_allow_revert(&mut self, tx: TypedTransaction, signer: impl Into<WalletSigner>) -> &mut Self {
+        self.txs.push(BundleTx { tx, signer: signer.into(), can_revert: true });
+        self
+    }
+
+    /// Adds a tip transfer to the builder, re-priced on every build
+    pub fn tip(&mut self, tip: BuilderTip) -> &mut Self {
+        self.tip = Some(tip);
+        self
+    }
+
+    pub fn fallback_gas_limit(&mut self, gas_limit: U256) -> &mut Self {
+        self.fallback_gas_limit = gas_limit;
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.txs.is_empty()
+    }
+
+    /// Fills nonce, gas price and gas limit from the chain's current state and signs every tx
+    pub async fn build(&self) -> Result<SignedBundle> {
+        let chain_id = self.provider.get_chainid().await?.as_u64();
+        let gas_price = self.provider.get_gas_price().await?;
+
+        // Gas first, a per-gas tip needs the total before it can be priced
+        let mut entries = Vec::with_capacity(self.txs.len() + 1);
+        let mut bundle_gas = U256::zero();
+        for entry in &self.txs {
+            let mut tx = entry.tx.clone();
+            tx.set_from(entry.signer.address());
+            tx.set_chain_id(chain_id);
+            tx.set_gas_price(gas_price);
+            if tx.gas().is_none() {
+                // Expected for a tx that depends on an earlier one; otherwise it likely reverts
+                let gas = match self.provider.estimate_gas(&tx, None).await {
+                    Ok(gas) => gas,
+                    Err(e) => {
+                        println!("⚠️ Could not estimate bundle tx {} ({}), assuming {} gas", entries.len(), e, self.fallback_gas_limit);
+                        self.fallback_gas_limit
+                    }
+                };
+                tx.set_gas(gas);
+            }
+            bundle_gas += tx.gas().copied().unwrap_or_default();
+            entries.push(BundleTx { tx, signer: entry.signer.clone(), can_revert: entry.can_revert });
+        }
+
+        if let Some(tip) = &self.tip {
+            let value = match tip.amount {
+                TipAmount::Fixed(amount) => amount,
+                TipAmount::PerGas(per_gas) => per_gas * bundle_gas,
+            };
+            let tx: TypedTransaction = TransactionRequest::pay(tip.to, value)
+                .from(tip.signer.address())
+                .chain_id(chain_id)
+                .gas_price(gas_price)
+                .gas(21_000u64)
+                .into();
+            let tip_tx = BundleTx { tx, signer: tip.signer.clone(), can_revert: false };
+            match tip.placement {
+                TipPlacement::First => entries.insert(0, tip_tx),
+                TipPlacement::Last => entries.push(tip_tx),
+            }
+        }
+
+        let mut next_nonces: HashMap<Address, U256> = HashMap::new();
+        let mut start_nonces = Vec::new();
+        let mut signed_txs = Vec::with_capacity(entries.len());
+        let mut reverting_tx_hashes = Vec::new();
+
+        for BundleTx { mut tx, signer, can_revert } in entries {
+            let signer = signer.with_chain_id(chain_id);
+            let sender = signer.address();
+
+            // Senders with several txs in the bundle (tip included) get consecutive nonces,
+            // starting after anything of theirs still in the mempool (e.g. an approve just sent)
+            let nonce = match next_nonces.get(&sender) {
+                Some(nonce) => *nonce,
+                None => {
+                    let nonce = self
+                        .provider
+                        .get_transaction_count(sender, Some(BlockNumber::Pending.into()))
+                        .await?;
+                    start_nonces.push((sender, nonce));
+                    nonce
+                }
+            };
+            next_nonces.insert(sender, nonce + 1);
+            tx.set_nonce(nonce);
+
+            let signature = signer
+                .sign_transaction(&tx)
+                .await
+                .context("Failed to sign bundle tx")?;
+            if can_revert {
+                reverting_tx_hashes.push(tx.hash(&signature));
+            }
+            signed_txs.push(format!("0x{}", hex::encode(tx.rlp_signed(&signature))));
+        }
+
+        Ok(SignedBundle {
+            txs: signed_txs,
+            start_nonces,
+            gas_price,
+            reverting_tx_hashes,
+        })
+    }
+}
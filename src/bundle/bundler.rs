@@ -1,8 +1,26 @@
 use ethers::prelude::*;
-use reqwest::Client;
-use serde_json::json;
 use std::sync::Arc;
-use chrono::Utc;
+
+use crate::bundle::relay::BundleRelay;
+use crate::bundle::types::{BundleReceipt, BundleRequest};
+use crate::bundle::{BloxrouteRelay, BundleBuilder, BundleSimulation, BundleStatus, SignedBundle, rebuild_bundle};
+
+/// Sends a bundle of signed transactions to the given relay, targeting the next block
+pub async fn send_bundle_next_block(
+    relay: &dyn BundleRelay,
+    signed_txs: Vec<String>, // hex strings of signed txs
+    provider: Arc<Provider<Http>>,
+) -> anyhow::Result<BundleReceipt> {
+    // Get current block
+    let block_number = provider.get_block_number().await?;
+    let bundle = BundleRequest::new(signed_txs, block_number.as_u64() + 1);
+
+    let receipt = relay.send_bundle(&bundle).await?;
+    if let Some(bundle_hash) = &receipt.bundle_hash {
+        println!("✅ Bundle accepted by {}! Hash: {}", receipt.relay, bundle_hash);
+    }
+    Ok(receipt)
+}
 
 /// Sends a bundle of signed transactions to BloxRoute on BSC
 pub async fn bundle_bsc_tx(
@@ -10,51 +28,61 @@ pub async fn bundle_bsc_tx(
     provider: Arc<Provider<Http>>,
     bloxroute_auth: &str,
 ) -> anyhow::Result<Option<String>> {
-    // Get current block
-    let block_number = provider.get_block_number().await?;
-    let target_block = format!("0x{:x}", block_number.as_u64() + 1);
-
-    // Prepare BloxRoute bundle payload
-    let bundle_params = json!({
-        "transaction": signed_txs,
-        "blockchain_network": "BSC-Mainnet",
-        "block_number": target_block,
-        "mev_builders": { "all": "" }
-    });
-
-    let payload = json!({
-        "jsonrpc": "2.0",
-        "method": "blxr_submit_bundle",
-        "params": bundle_params,
-        "id": chrono::Utc::now().timestamp()
-    });
-
-    // Send request
-    let client = Client::new();
-    let res = client
-        .post("https://api.blxrbdn.com")
-        .header("Content-Type", "application/json")
-        .header("Authorization", bloxroute_auth)
-        .json(&payload)
-        .send()
-        .await?;
-
-    let status = res.status();
-    let text = res.text().await?;
-    println!("BloxRoute response (status {}): {}", status, text);
-
-    // Parse response JSON
-    let resp_json: serde_json::Value = serde_json::from_str(&text)?;
-    if status.is_success() {
-        if let Some(bundle_hash) = resp_json["result"]["bundleHash"].as_str() {
-            println!("✅ Bundle accepted! Hash: {}", bundle_hash);
-            return Ok(Some(bundle_hash.to_string()));
-        } else if let Some(result_str) = resp_json["result"].as_str() {
-            return Ok(Some(result_str.to_string()));
+    let relay = BloxrouteRelay::new(bloxroute_auth);
+    let receipt = send_bundle_next_block(&relay, signed_txs, provider).await?;
+    Ok(receipt.bundle_hash)
+}
+
+/// Targets each of the next `window` blocks in turn, resubmitting until the bundle lands.
+/// Starts with `signed` and rebuilds it from `builder` (nonces, gas) whenever it goes stale,
+/// simulating every rebuild against `simulation` when given.
+pub async fn submit_bundle_window(
+    relay: &dyn BundleRelay,
+    builder: &BundleBuilder,
+    signed: SignedBundle,
+    provider: Arc<Provider<Http>>,
+    window: u64,
+    simulation: Option<&BundleSimulation<'_>>,
+) -> anyhow::Result<BundleStatus> {
+    let mut signed = signed;
+    let first_block = provider.get_block_number().await?.as_u64() + 1;
+    let last_block = first_block + window.max(1) - 1;
+    let mut target = first_block;
+
+    while target <= last_block {
+        // Missed the slot while checking the previous block, move on to the next one
+        let head = provider.get_block_number().await?.as_u64();
+        if head >= target {
+            target = head + 1;
+            continue;
+        }
+
+        if signed.is_stale(&provider).await? {
+            signed = rebuild_bundle(builder, provider.clone(), simulation).await?;
+        }
+
+        match relay.send_bundle(&signed.request(target)).await {
+            Ok(receipt) => println!(
+                "📦 Submitted to {} for block {} (hash: {:?})",
+                receipt.relay, target, receipt.bundle_hash
+            ),
+            // Bad auth, a rejected bundle etc. won't go better on the next block
+            Err(e) if !e.is_retryable() => return Err(e.into()),
+            Err(e) => println!("❌ Bundle for block {} not accepted: {}", target, e),
+        }
+
+        // Bounded by the window so a missed block reads as pending, not expired
+        let status = signed
+            .tracker(provider.clone(), target)?
+            .until_block(last_block)
+            .check_block(target)
+            .await?;
+        println!("{}", status);
+        if status.is_final() {
+            return Ok(status);
         }
-    } else if let Some(error) = resp_json["error"].as_object() {
-        println!("❌ BloxRoute error: {:?}", error);
+        target += 1;
     }
 
-    Ok(None)
+    Ok(BundleStatus::Expired { last_block })
 }
@@ -0,0 +1,55 @@
+use async_trait::async_trait;
+use reqwest::Client;
+
+use crate::bundle::relay::{BundleRelay, post_json_rpc};
+use crate::bundle::types::{BundleError, BundleReceipt, BundleRequest, EthSendBundleParams, EthSendBundleResult};
+
+pub const CLUB48_URL: &str = "https://puissant-builder.48.club";
+
+/// 48Club puissant builder (`eth_sendBundle`, no auth header)
+pub struct Club48Relay {
+    client: Client,
+    url: String,
+}
+
+impl Club48Relay {
+    pub fn new(url: Option<String>) -> Self {
+        Self {
+            client: Client::new(),
+            url: url.unwrap_or_else(|| CLUB48_URL.to_string()),
+        }
+    }
+
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.client = client;
+        self
+    }
+}
+
+#[async_trait]
+impl BundleRelay for Club48Relay {
+    fn name(&self) -> &str {
+        "48club"
+    }
+
+    async fn send_bundle(&self, bundle: &BundleRequest) -> Result<BundleReceipt, BundleError> {
+        // 48Club drops bundles after maxTimestamp, give it one slot of slack per block
+        let blocks = bundle.last_block() - bundle.block_number + 1;
+        let params = EthSendBundleParams {
+            txs: bundle.txs.clone(),
+            max_block_number: Some(bundle.last_block()),
+            max_timestamp: Some(chrono::Utc::now().timestamp() as u64 + 3 * blocks + 3),
+            reverting_tx_hashes: bundle.reverting_tx_hashes.clone(),
+            ..Default::default()
+        };
+
+        let result: EthSendBundleResult =
+            post_json_rpc(&self.client, self.name(), &self.url, None, "eth_sendBundle", [params]).await?;
+
+        Ok(BundleReceipt {
+            relay: self.name().to_string(),
+            bundle_hash: Some(result.into_hash()),
+            block_number: bundle.block_number,
+        })
+    }
+}
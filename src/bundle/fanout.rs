@@ -0,0 +1,164 @@
+use ethers::prelude::*;
+use futures::future::join_all;
+use std::{collections::BTreeMap, sync::Arc, time::{Duration, Instant}};
+
+use crate::bundle::relay::BundleRelay;
+use crate::bundle::types::{BundleError, BundleReceipt, BundleRequest};
+use crate::bundle::{BundleBuilder, BundleSimulation, BundleStatus, SignedBundle, rebuild_bundle};
+
+/// One relay's answer to one submission
+#[derive(Debug)]
+pub struct RelayOutcome {
+    pub relay: String,
+    pub block_number: u64,
+    pub result: Result<BundleReceipt, BundleError>,
+    pub latency: Duration,
+}
+
+impl RelayOutcome {
+    pub fn accepted(&self) -> bool {
+        self.result.is_ok()
+    }
+}
+
+/// Per-relay totals over a whole fan-out run
+#[derive(Debug, Clone, Default)]
+pub struct RelayStats {
+    pub accepted: u32,
+    pub rejected: u32,
+    pub total_latency: Duration,
+    pub last_error: Option<String>,
+}
+
+impl RelayStats {
+    pub fn avg_latency(&self) -> Duration {
+        let sent = self.accepted + self.rejected;
+        if sent == 0 { Duration::ZERO } else { self.total_latency / sent }
+    }
+}
+
+/// Aggregates outcomes per relay
+pub fn summarize_outcomes(outcomes: &[RelayOutcome]) -> BTreeMap<String, RelayStats> {
+    let mut stats: BTreeMap<String, RelayStats> = BTreeMap::new();
+    for outcome in outcomes {
+        let entry = stats.entry(outcome.relay.clone()).or_default();
+        entry.total_latency += outcome.latency;
+        match &outcome.result {
+            Ok(_) => entry.accepted += 1,
+            Err(e) => {
+                entry.rejected += 1;
+                entry.last_error = Some(e.to_string());
+            }
+        }
+    }
+    stats
+}
+
+pub fn print_relay_summary(outcomes: &[RelayOutcome]) {
+    for (relay, stats) in summarize_outcomes(outcomes) {
+        println!(
+            "📡 {:<10} accepted {} | rejected {} | avg latency {:?}{}",
+            relay,
+            stats.accepted,
+            stats.rejected,
+            stats.avg_latency(),
+            stats.last_error.map(|e| format!(" | last error: {}", e)).unwrap_or_default()
+        );
+    }
+}
+
+/// Sends the same bundle to every relay at once and waits for all answers
+pub async fn fan_out_bundle(relays: &[Box<dyn BundleRelay>], bundle: &BundleRequest) -> Vec<RelayOutcome> {
+    let relays: Vec<&dyn BundleRelay> = relays.iter().map(|relay| relay.as_ref()).collect();
+    send_to_all(&relays, bundle).await
+}
+
+pub(crate) async fn send_to_all(relays: &[&dyn BundleRelay], bundle: &BundleRequest) -> Vec<RelayOutcome> {
+    join_all(relays.iter().map(|relay| async move {
+        let start = Instant::now();
+        let result = relay.send_bundle(bundle).await;
+        RelayOutcome {
+            relay: relay.name().to_string(),
+            block_number: bundle.block_number,
+            result,
+            latency: start.elapsed(),
+        }
+    }))
+    .await
+}
+
+/// Drops every relay that just failed with a non-retryable error, it would only fail again
+pub(crate) fn drop_failed_relays(active: &mut Vec<&dyn BundleRelay>, sent: &[RelayOutcome]) {
+    for outcome in sent {
+        if let Err(e) = &outcome.result
+            && !e.is_retryable()
+        {
+            println!("❌ Dropping {} for the rest of the window: {}", outcome.relay, e);
+            active.retain(|relay| relay.name() != outcome.relay);
+        }
+    }
+}
+
+/// `submit_bundle_window` across several relays: every block is fanned out to all of them,
+/// and slow submissions still in flight are dropped as soon as the tracker sees the bundle settle.
+/// A relay that fails with a non-retryable error sits out the rest of the window.
+pub async fn fan_out_bundle_window(
+    relays: &[Box<dyn BundleRelay>],
+    builder: &BundleBuilder,
+    signed: SignedBundle,
+    provider: Arc<Provider<Http>>,
+    window: u64,
+    simulation: Option<&BundleSimulation<'_>>,
+) -> anyhow::Result<(BundleStatus, Vec<RelayOutcome>)> {
+    let mut signed = signed;
+    let mut outcomes = Vec::new();
+    let first_block = provider.get_block_number().await?.as_u64() + 1;
+    let last_block = first_block + window.max(1) - 1;
+    let mut target = first_block;
+    let mut active: Vec<&dyn BundleRelay> = relays.iter().map(|relay| relay.as_ref()).collect();
+
+    while target <= last_block {
+        if active.is_empty() {
+            println!("❌ No relay left to submit to, giving up on the window");
+            return Ok((BundleStatus::Expired { last_block: target - 1 }, outcomes));
+        }
+        let head = provider.get_block_number().await?.as_u64();
+        if head >= target {
+            target = head + 1;
+            continue;
+        }
+
+        if signed.is_stale(&provider).await? {
+            signed = rebuild_bundle(builder, provider.clone(), simulation).await?;
+        }
+
+        let request = signed.request(target);
+        let tracker = signed.tracker(provider.clone(), target)?.until_block(last_block);
+        let mut submissions = Box::pin(send_to_all(&active, &request));
+        let mut check = Box::pin(tracker.check_block(target));
+
+        let (status, sent) = tokio::select! {
+            sent = &mut submissions => (check.await?, sent),
+            status = &mut check => {
+                let status = status?;
+                if status.is_final() {
+                    println!("🛑 Bundle settled, dropping outstanding relay submissions");
+                    (status, Vec::new())
+                } else {
+                    (status, (&mut submissions).await)
+                }
+            }
+        };
+        drop(submissions);
+        drop_failed_relays(&mut active, &sent);
+        outcomes.extend(sent);
+
+        println!("{}", status);
+        if status.is_final() {
+            return Ok((status, outcomes));
+        }
+        target += 1;
+    }
+
+    Ok((BundleStatus::Expired { last_block }, outcomes))
+}
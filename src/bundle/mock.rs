@@ -0,0 +1,248 @@
+use anyhow::{Context, Result};
+use ethers::prelude::*;
+use serde_json::{Value, json};
+use std::{
+    collections::VecDeque,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+
+use crate::bundle::types::BundleRequest;
+
+/// Failure the mock relay applies to the next bundle it receives
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MockFailure {
+    /// Answer 401 with an auth error
+    AuthError,
+    /// Sit on the request this long before answering normally
+    Timeout(Duration),
+    /// Accept the bundle but let its target block pass without it
+    DropBundle,
+}
+
+struct MockState {
+    node: Provider<Http>,
+    auth: Option<String>,
+    failures: Mutex<VecDeque<MockFailure>>,
+    received: Mutex<Vec<BundleRequest>>,
+}
+
+/// Local stand-in for a bundle relay, backed by an anvil node with automine off.
+/// Speaks `blxr_submit_bundle` and `eth_sendBundle` and mines each accepted bundle
+/// in exactly the block it targets.
+pub struct MockRelay {
+    addr: SocketAddr,
+    state: Arc<MockState>,
+    server: JoinHandle<()>,
+}
+
+impl MockRelay {
+    /// Starts listening on a random local port and turns off automine on `node_url`
+    pub async fn start(node_url: &str, auth: Option<String>) -> Result<Self> {
+        let node = Provider::<Http>::try_from(node_url)?;
+        node.request::<_, ()>("evm_setAutomine", [false])
+            .await
+            .context("Mock relay node must be anvil (evm_setAutomine failed)")?;
+
+        let state = Arc::new(MockState {
+            node,
+            auth,
+            failures: Mutex::new(VecDeque::new()),
+            received: Mutex::new(Vec::new()),
+        });
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+
+        let server_state = state.clone();
+        let server = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let state = server_state.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = handle_connection(stream, state).await {
+                        println!("⚠️ Mock relay connection error: {}", e);
+                    }
+                });
+            }
+        });
+
+        Ok(Self { addr, state, server })
+    }
+
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// Queues a failure for the next bundle, failures apply in the order queued
+    pub fn fail_next(&self, failure: MockFailure) {
+        self.state.failures.lock().unwrap().push_back(failure);
+    }
+
+    /// Every bundle the relay accepted so far
+    pub fn received(&self) -> Vec<BundleRequest> {
+        self.state.received.lock().unwrap().clone()
+    }
+
+    /// Mines `blocks` empty blocks, e.g. to move past a dropped bundle's target
+    pub async fn mine_blocks(&self, blocks: u64) -> Result<()> {
+        for _ in 0..blocks {
+            self.state.node.request::<_, Value>("evm_mine", ()).await?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for MockRelay {
+    fn drop(&mut self) {
+        self.server.abort();
+    }
+}
+
+async fn handle_connection(stream: TcpStream, state: Arc<MockState>) -> Result<()> {
+    let mut reader = BufReader::new(stream);
+    let request = read_http_request(&mut reader).await?;
+    let authorization = request.header("authorization").map(str::to_string);
+    let (status, response) = handle_rpc(&state, authorization, &request.body).await;
+    write_http_json(reader.get_mut(), status, &response).await
+}
+
+/// Request as the mock servers see it
+pub(crate) struct HttpRequest {
+    pub path: String,
+    pub headers: Vec<(String, String)>, // names lowercased
+    pub body: Vec<u8>,
+}
+
+impl HttpRequest {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter().find(|(n, _)| n == name).map(|(_, v)| v.as_str())
+    }
+}
+
+/// Minimal HTTP/1.1 request: request line, headers, Content-Length body
+pub(crate) async fn read_http_request(reader: &mut BufReader<TcpStream>) -> Result<HttpRequest> {
+    let mut line = String::new();
+    reader.read_line(&mut line).await?;
+    let path = line.split_whitespace().nth(1).unwrap_or("/").to_string();
+    let mut headers = Vec::new();
+    loop {
+        line.clear();
+        reader.read_line(&mut line).await?;
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            headers.push((name.trim().to_ascii_lowercase(), value.trim().to_string()));
+        }
+    }
+    let mut request = HttpRequest { path, headers, body: Vec::new() };
+    let content_length = request.header("content-length").unwrap_or("0").parse()?;
+    request.body = vec![0u8; content_length];
+    reader.read_exact(&mut request.body).await?;
+    Ok(request)
+}
+
+pub(crate) async fn write_http_json(stream: &mut TcpStream, status: &str, response: &Value) -> Result<()> {
+    let payload = response.to_string();
+    let reply = format!(
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        payload.len(),
+        payload
+    );
+    stream.write_all(reply.as_bytes()).await?;
+    Ok(())
+}
+
+async fn handle_rpc(state: &Arc<MockState>, authorization: Option<String>, body: &[u8]) -> (&'static str, Value) {
+    let request: Value = match serde_json::from_slice(body) {
+        Ok(request) => request,
+        Err(e) => return ("400 Bad Request", rpc_error(Value::Null, -32700, &e.to_string())),
+    };
+    let id = request["id"].clone();
+
+    let failure = state.failures.lock().unwrap().pop_front();
+    let auth_ok = state.auth.is_none() || state.auth == authorization;
+    if failure == Some(MockFailure::AuthError) || !auth_ok {
+        return ("401 Unauthorized", rpc_error(id, -32001, "invalid auth header"));
+    }
+    if let Some(MockFailure::Timeout(delay)) = failure {
+        tokio::time::sleep(delay).await;
+    }
+
+    let head = match state.node.get_block_number().await {
+        Ok(head) => head.as_u64(),
+        Err(e) => return ("200 OK", rpc_error(id, -32000, &e.to_string())),
+    };
+    let (bundle, result) = match request["method"].as_str() {
+        Some("blxr_submit_bundle") => {
+            let params = &request["params"];
+            let block = params["block_number"]
+                .as_str()
+                .and_then(|b| u64::from_str_radix(b.trim_start_matches("0x"), 16).ok())
+                .unwrap_or(head + 1);
+            let bundle = BundleRequest::new(string_list(&params["transaction"]), block);
+            let hash = format!("{:?}", H256::random());
+            (bundle, json!({ "bundleHash": hash }))
+        }
+        Some("eth_sendBundle") => {
+            let params = &request["params"][0];
+            let max_block = params["maxBlockNumber"].as_u64();
+            let block = params["blockNumber"].as_u64().or(max_block).unwrap_or(head + 1);
+            let mut bundle = BundleRequest::new(string_list(&params["txs"]), block);
+            bundle.max_block_number = max_block;
+            (bundle, json!(format!("{:?}", H256::random())))
+        }
+        other => {
+            let message = format!("method {:?} not supported by mock relay", other);
+            return ("200 OK", rpc_error(id, -32601, &message));
+        }
+    };
+
+    if bundle.block_number <= head {
+        let message = format!("block number {} is stale, head is {}", bundle.block_number, head);
+        return ("200 OK", rpc_error(id, -32000, &message));
+    }
+
+    state.received.lock().unwrap().push(bundle.clone());
+    let dropped = failure == Some(MockFailure::DropBundle);
+    let state = state.clone();
+    tokio::spawn(async move {
+        if let Err(e) = mine_bundle(&state.node, &bundle, dropped).await {
+            println!("⚠️ Mock relay failed to mine bundle: {}", e);
+        }
+    });
+
+    ("200 OK", json!({ "jsonrpc": "2.0", "id": id, "result": result }))
+}
+
+/// Mines empty blocks up to `block_number - 1`, then `block_number` with the bundle's txs
+/// (or empty when the bundle is dropped)
+async fn mine_bundle(node: &Provider<Http>, bundle: &BundleRequest, dropped: bool) -> Result<()> {
+    while node.get_block_number().await?.as_u64() + 1 < bundle.block_number {
+        node.request::<_, Value>("evm_mine", ()).await?;
+    }
+    if !dropped {
+        for tx in &bundle.txs {
+            let raw = Bytes::from(hex::decode(tx.trim_start_matches("0x"))?);
+            node.send_raw_transaction(raw).await?;
+        }
+    }
+    node.request::<_, Value>("evm_mine", ()).await?;
+    Ok(())
+}
+
+fn string_list(value: &Value) -> Vec<String> {
+    value
+        .as_array()
+        .map(|items| items.iter().filter_map(|v| v.as_str().map(str::to_string)).collect())
+        .unwrap_or_default()
+}
+
+pub(crate) fn rpc_error(id: Value, code: i64, message: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
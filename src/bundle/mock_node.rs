@@ -0,0 +1,316 @@
+use anyhow::{Context, Result};
+use ethers::prelude::*;
+use ethers::utils::rlp;
+use serde_json::{Value, json};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+};
+use tokio::io::BufReader;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+
+use crate::bundle::mock::{read_http_request, rpc_error, write_http_json};
+
+/// Failure the mock node applies to the next raw tx it receives
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NodeFailure {
+    /// Refuse the tx with this error, it never reaches the pool
+    Reject(String),
+    /// Take the tx into the pool but answer with this error, like a send that timed out late
+    AcceptThenError(String),
+}
+
+#[derive(Default)]
+struct NodeState {
+    chain_id: u64,
+    gas_price: U256,
+    block: u64,
+    automine: bool,
+    balances: HashMap<Address, U256>,
+    nonces: HashMap<Address, U256>,
+    code: HashMap<Address, Bytes>,
+    call_results: HashMap<Address, Bytes>,
+    reverting: HashSet<Address>,
+    pool: Vec<Transaction>,
+    mined: HashMap<H256, (Transaction, TransactionReceipt)>,
+    received: Vec<Transaction>,
+    failures: VecDeque<NodeFailure>,
+}
+
+/// In-memory chain behind a JSON-RPC endpoint, for tests that can't count on anvil.
+/// Plain value transfers only: every tx is charged its full gas limit, calls to an address
+/// marked with `revert_calls_to` mine with `status == 0`, and nothing else executes.
+/// `eth_call` answers whatever was canned for the target with `set_call_result`.
+/// Speaks the methods the crate uses plus `evm_mine` and `evm_setAutomine`, so it can back
+/// a `MockRelay` too.
+pub struct MockNode {
+    addr: SocketAddr,
+    state: Arc<Mutex<NodeState>>,
+    server: JoinHandle<()>,
+}
+
+impl MockNode {
+    /// Starts listening on a random local port, automine on and gas at 1 gwei
+    pub async fn start(chain_id: u64) -> Result<Self> {
+        let state = Arc::new(Mutex::new(NodeState {
+            chain_id,
+            gas_price: U256::exp10(9),
+            automine: true,
+            ..Default::default()
+        }));
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+
+        let server_state = state.clone();
+        let server = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let state = server_state.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = handle_connection(stream, state).await {
+                        println!("⚠️ Mock node connection error: {}", e);
+                    }
+                });
+            }
+        });
+
+        Ok(Self { addr, state, server })
+    }
+
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    pub fn set_balance(&self, address: Address, balance: U256) {
+        self.state.lock().unwrap().balances.insert(address, balance);
+    }
+
+    pub fn balance(&self, address: Address) -> U256 {
+        self.state.lock().unwrap().balances.get(&address).copied().unwrap_or_default()
+    }
+
+    pub fn set_code(&self, address: Address, code: Bytes) {
+        self.state.lock().unwrap().code.insert(address, code);
+    }
+
+    /// Every `eth_call` to `address` returns `output`, whatever the calldata
+    pub fn set_call_result(&self, address: Address, output: Bytes) {
+        self.state.lock().unwrap().call_results.insert(address, output);
+    }
+
+    pub fn set_gas_price(&self, gas_price: U256) {
+        self.state.lock().unwrap().gas_price = gas_price;
+    }
+
+    /// Every tx sent to `address` from now on is mined as reverted
+    pub fn revert_calls_to(&self, address: Address) {
+        self.state.lock().unwrap().reverting.insert(address);
+    }
+
+    /// Queues a failure for the next raw tx, failures apply in the order queued
+    pub fn fail_next(&self, failure: NodeFailure) {
+        self.state.lock().unwrap().failures.push_back(failure);
+    }
+
+    pub fn set_automine(&self, automine: bool) {
+        self.state.lock().unwrap().automine = automine;
+    }
+
+    /// Mines one block with every pooled tx that can execute
+    pub fn mine(&self) {
+        self.state.lock().unwrap().mine();
+    }
+
+    /// Forgets every pooled tx, like a node restart losing its mempool
+    pub fn drop_pool(&self) {
+        self.state.lock().unwrap().pool.clear();
+    }
+
+    /// Every tx the node took into its pool so far, in arrival order
+    pub fn received(&self) -> Vec<Transaction> {
+        self.state.lock().unwrap().received.clone()
+    }
+
+    /// Every tx mined so far, in mining order
+    pub fn mined(&self) -> Vec<Transaction> {
+        let state = self.state.lock().unwrap();
+        let mut mined: Vec<Transaction> = state.mined.values().map(|(tx, _)| tx.clone()).collect();
+        mined.sort_by_key(|tx| (tx.block_number, tx.transaction_index));
+        mined
+    }
+}
+
+impl Drop for MockNode {
+    fn drop(&mut self) {
+        self.server.abort();
+    }
+}
+
+impl NodeState {
+    fn nonce(&self, address: Address) -> U256 {
+        self.nonces.get(&address).copied().unwrap_or_default()
+    }
+
+    fn balance(&self, address: Address) -> U256 {
+        self.balances.get(&address).copied().unwrap_or_default()
+    }
+
+    /// Mined nonce plus every pooled tx that follows it without a gap
+    fn pending_nonce(&self, address: Address) -> U256 {
+        let mut nonce = self.nonce(address);
+        while self.pool.iter().any(|tx| tx.from == address && tx.nonce == nonce) {
+            nonce += U256::one();
+        }
+        nonce
+    }
+
+    fn max_cost(tx: &Transaction) -> U256 {
+        tx.value + tx.gas * tx.gas_price.or(tx.max_fee_per_gas).unwrap_or_default()
+    }
+
+    fn send_raw(&mut self, raw: &[u8]) -> Result<H256, String> {
+        let failure = self.failures.pop_front();
+        if let Some(NodeFailure::Reject(error)) = failure {
+            return Err(error);
+        }
+
+        let mut tx: Transaction = rlp::decode(raw).map_err(|e| format!("invalid tx: {}", e))?;
+        tx.recover_from_mut().map_err(|e| format!("invalid signature: {}", e))?;
+        if self.mined.contains_key(&tx.hash) || self.pool.iter().any(|p| p.hash == tx.hash) {
+            return Err("already known".to_string());
+        }
+        if tx.nonce < self.nonce(tx.from) {
+            return Err("nonce too low".to_string());
+        }
+        if self.balance(tx.from) < Self::max_cost(&tx) {
+            return Err("insufficient funds for gas * price + value".to_string());
+        }
+
+        let tx_hash = tx.hash;
+        self.pool.push(tx.clone());
+        self.received.push(tx);
+        if self.automine {
+            self.mine();
+        }
+        match failure {
+            Some(NodeFailure::AcceptThenError(error)) => Err(error),
+            _ => Ok(tx_hash),
+        }
+    }
+
+    fn mine(&mut self) {
+        self.block += 1;
+        let mut index = 0u64;
+        let mut cumulative_gas = U256::zero();
+        while let Some(position) = self
+            .pool
+            .iter()
+            .position(|tx| tx.nonce == self.nonce(tx.from) && self.balance(tx.from) >= Self::max_cost(tx))
+        {
+            let mut tx = self.pool.remove(position);
+            let gas_price = tx.gas_price.or(tx.max_fee_per_gas).unwrap_or_default();
+            let reverted = tx.to.is_some_and(|to| self.reverting.contains(&to));
+
+            let fee = tx.gas * gas_price;
+            let spent = if reverted { fee } else { fee + tx.value };
+            self.balances.insert(tx.from, self.balance(tx.from) - spent);
+            if let (false, Some(to)) = (reverted, tx.to) {
+                self.balances.insert(to, self.balance(to) + tx.value);
+            }
+            self.nonces.insert(tx.from, tx.nonce + 1);
+
+            cumulative_gas += tx.gas;
+            tx.block_number = Some(self.block.into());
+            tx.block_hash = Some(H256::from_low_u64_be(self.block));
+            tx.transaction_index = Some(index.into());
+            let receipt = TransactionReceipt {
+                transaction_hash: tx.hash,
+                transaction_index: index.into(),
+                block_hash: tx.block_hash,
+                block_number: tx.block_number,
+                from: tx.from,
+                to: tx.to,
+                cumulative_gas_used: cumulative_gas,
+                gas_used: Some(tx.gas),
+                effective_gas_price: Some(gas_price),
+                status: Some(if reverted { U64::zero() } else { U64::one() }),
+                transaction_type: tx.transaction_type,
+                ..Default::default()
+            };
+            self.mined.insert(tx.hash, (tx, receipt));
+            index += 1;
+        }
+    }
+}
+
+async fn handle_connection(stream: TcpStream, state: Arc<Mutex<NodeState>>) -> Result<()> {
+    let mut reader = BufReader::new(stream);
+    let request = read_http_request(&mut reader).await?;
+    let response = match serde_json::from_slice::<Value>(&request.body) {
+        Ok(request) => handle_rpc(&state, &request),
+        Err(e) => rpc_error(Value::Null, -32700, &e.to_string()),
+    };
+    write_http_json(reader.get_mut(), "200 OK", &response).await
+}
+
+fn handle_rpc(state: &Mutex<NodeState>, request: &Value) -> Value {
+    let id = request["id"].clone();
+    let params = &request["params"];
+    let mut state = state.lock().unwrap();
+    let result = match request["method"].as_str().unwrap_or_default() {
+        "eth_chainId" => Ok(json!(U64::from(state.chain_id))),
+        "eth_blockNumber" => Ok(json!(U64::from(state.block))),
+        "eth_gasPrice" => Ok(json!(state.gas_price)),
+        "eth_estimateGas" => {
+            let has_input = params[0]["input"].as_str().or(params[0]["data"].as_str()).is_some_and(|d| d.len() > 2);
+            Ok(json!(U256::from(if has_input { 100_000u64 } else { 21_000u64 })))
+        }
+        "eth_getBalance" => address_param(params).map(|a| json!(state.balance(a))),
+        "eth_getCode" => address_param(params).map(|a| json!(state.code.get(&a).cloned().unwrap_or_default())),
+        "eth_getTransactionCount" => address_param(params).map(|a| match params[1].as_str() {
+            Some("pending") => json!(state.pending_nonce(a)),
+            _ => json!(state.nonce(a)),
+        }),
+        "eth_call" => params[0]["to"]
+            .as_str()
+            .and_then(|to| to.parse::<Address>().ok())
+            .and_then(|to| state.call_results.get(&to))
+            .map(|output| json!(output))
+            .ok_or((3, "execution reverted".to_string())),
+        "eth_sendRawTransaction" => params[0]
+            .as_str()
+            .context("missing raw tx")
+            .and_then(|raw| Ok::<_, anyhow::Error>(hex::decode(raw.trim_start_matches("0x"))?))
+            .map_err(|e| e.to_string())
+            .and_then(|raw| state.send_raw(&raw))
+            .map(|hash| json!(hash))
+            .map_err(|e| (-32000, e)),
+        "eth_getTransactionByHash" => hash_param(params).map(|h| {
+            let pooled = state.pool.iter().find(|tx| tx.hash == h).cloned();
+            json!(pooled.or_else(|| state.mined.get(&h).map(|(tx, _)| tx.clone())))
+        }),
+        "eth_getTransactionReceipt" => hash_param(params).map(|h| json!(state.mined.get(&h).map(|(_, receipt)| receipt))),
+        "evm_setAutomine" => {
+            state.automine = params[0].as_bool().unwrap_or(true);
+            Ok(Value::Null)
+        }
+        "evm_mine" => {
+            state.mine();
+            Ok(json!("0x0"))
+        }
+        other => Err((-32601, format!("method {:?} not supported by mock node", other))),
+    };
+    match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err((code, message)) => rpc_error(id, code, &message),
+    }
+}
+
+fn address_param(params: &Value) -> Result<Address, (i64, String)> {
+    params[0].as_str().and_then(|a| a.parse().ok()).ok_or((-32602, "invalid address".to_string()))
+}
+
+fn hash_param(params: &Value) -> Result<H256, (i64, String)> {
+    params[0].as_str().and_then(|h| h.parse().ok()).ok_or((-32602, "invalid hash".to_string()))
+}
@@ -1,6 +1,36 @@
 pub mod bundler;
 pub mod types;
+pub mod relay;
+pub mod bloxroute;
+pub mod club48;
+pub mod blockrazor;
+pub mod bep322;
+pub mod tracker;
+pub mod builder;
+pub mod simulate;
+pub mod fanout;
+#[cfg(any(test, feature = "mock"))]
+pub mod mock;
+#[cfg(any(test, feature = "mock"))]
+pub mod mock_node;
+pub mod private;
+pub mod timing;
 
 
 pub use bundler::*;
-pub use types::*;
\ No newline at end of file
+pub use types::*;
+pub use relay::*;
+pub use bloxroute::*;
+pub use club48::*;
+pub use blockrazor::*;
+pub use bep322::*;
+pub use tracker::*;
+pub use builder::*;
+pub use simulate::*;
+pub use fanout::*;
+#[cfg(any(test, feature = "mock"))]
+pub use mock::*;
+#[cfg(any(test, feature = "mock"))]
+pub use mock_node::*;
+pub use private::*;
+pub use timing::*;
@@ -0,0 +1,243 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use ethers::prelude::*;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::{env, sync::Arc, time::{Duration, Instant}};
+
+use crate::bundle::BLOXROUTE_URL;
+use crate::bundle::relay::post_json_rpc;
+use crate::bundle::types::BundleError;
+
+/// An endpoint that takes a single signed tx and keeps it out of the public mempool
+#[async_trait]
+pub trait PrivateTxRelay: Send + Sync {
+    fn name(&self) -> &str;
+
+    async fn send_private_tx(&self, raw_tx: &str) -> Result<H256, BundleError>;
+}
+
+#[derive(Debug, Serialize)]
+struct BlxrPrivateTxParams {
+    transaction: String, // signed tx without the 0x prefix
+}
+
+#[derive(Debug, Deserialize)]
+struct BlxrPrivateTxResult {
+    #[serde(rename = "txHash")]
+    tx_hash: String,
+}
+
+/// bloXroute `blxr_private_tx`
+pub struct BloxroutePrivateTx {
+    client: Client,
+    url: String,
+    auth: String,
+}
+
+impl BloxroutePrivateTx {
+    pub fn new(auth: &str) -> Self {
+        Self {
+            client: Client::new(),
+            url: BLOXROUTE_URL.to_string(),
+            auth: auth.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl PrivateTxRelay for BloxroutePrivateTx {
+    fn name(&self) -> &str {
+        "bloxroute"
+    }
+
+    async fn send_private_tx(&self, raw_tx: &str) -> Result<H256, BundleError> {
+        let params = BlxrPrivateTxParams {
+            transaction: raw_tx.trim_start_matches("0x").to_string(),
+        };
+        let result: BlxrPrivateTxResult = post_json_rpc(
+            &self.client,
+            self.name(),
+            &self.url,
+            Some(&self.auth),
+            "blxr_private_tx",
+            params,
+        )
+        .await?;
+        parse_tx_hash(self.name(), &result.tx_hash)
+    }
+}
+
+/// Builder `eth_sendPrivateRawTransaction` (48Club, BlockRazor and most BEP-322 builders)
+pub struct BuilderPrivateTx {
+    client: Client,
+    url: String,
+    auth: Option<String>,
+}
+
+impl BuilderPrivateTx {
+    pub fn new(url: &str, auth: Option<String>) -> Self {
+        Self {
+            client: Client::new(),
+            url: url.to_string(),
+            auth,
+        }
+    }
+}
+
+#[async_trait]
+impl PrivateTxRelay for BuilderPrivateTx {
+    fn name(&self) -> &str {
+        "builder"
+    }
+
+    async fn send_private_tx(&self, raw_tx: &str) -> Result<H256, BundleError> {
+        let hash: String = post_json_rpc(
+            &self.client,
+            self.name(),
+            &self.url,
+            self.auth.as_deref(),
+            "eth_sendPrivateRawTransaction",
+            [raw_tx],
+        )
+        .await?;
+        parse_tx_hash(self.name(), &hash)
+    }
+}
+
+fn parse_tx_hash(relay: &str, hash: &str) -> Result<H256, BundleError> {
+    let hash = if hash.starts_with("0x") { hash.to_string() } else { format!("0x{}", hash) };
+    hash.parse().map_err(|_| BundleError::InvalidResponse {
+        relay: relay.to_string(),
+        status: 200,
+        body: hash.clone(),
+    })
+}
+
+/// Public rebroadcast of a private tx that isn't mined within `after`, or that the relay refused
+#[derive(Clone)]
+pub struct PublicFallback {
+    pub after: Duration,
+    pub provider: Arc<Provider<Http>>, // owned, the rebroadcast outlives the send call
+}
+
+/// How single txs leave the box
+#[derive(Clone)]
+pub enum SendMode {
+    /// Regular `eth_sendRawTransaction` through the RPC node
+    Public,
+    /// Private relay, optionally rebroadcast publicly in the background
+    Private {
+        relay: Arc<dyn PrivateTxRelay>,
+        fallback: Option<PublicFallback>,
+    },
+}
+
+impl SendMode {
+    /// `TX_SEND_MODE` = public (default) | bloxroute | builder.
+    /// `builder` needs `PRIVATE_TX_URL` (optional `PRIVATE_TX_AUTH_HEADER`),
+    /// `PRIVATE_TX_FALLBACK_SECS` enables the public fallback through `RPC_URL`.
+    pub fn from_env() -> Result<Self> {
+        dotenvy::dotenv().ok();
+        let mode = env::var("TX_SEND_MODE").unwrap_or_else(|_| "public".to_string());
+        let relay: Arc<dyn PrivateTxRelay> = match mode.trim().to_ascii_lowercase().as_str() {
+            "public" => return Ok(SendMode::Public),
+            "bloxroute" => {
+                let auth = env::var("BLOXROUTE_AUTH_HEADER")
+                    .context("Missing BLOXROUTE_AUTH_HEADER in environment (.env)")?;
+                Arc::new(BloxroutePrivateTx::new(&auth))
+            }
+            "builder" => {
+                let url = env::var("PRIVATE_TX_URL").context("Missing PRIVATE_TX_URL in environment (.env)")?;
+                Arc::new(BuilderPrivateTx::new(&url, env::var("PRIVATE_TX_AUTH_HEADER").ok()))
+            }
+            other => anyhow::bail!("Unknown TX_SEND_MODE: {}", other),
+        };
+        let fallback = match env::var("PRIVATE_TX_FALLBACK_SECS") {
+            Ok(secs) => {
+                let rpc_url = env::var("RPC_URL").unwrap_or_else(|_| "https://bsc-dataseed.binance.org".to_string());
+                let provider = Provider::<Http>::try_from(rpc_url.as_str())
+                    .context("Failed to create HTTP provider from RPC_URL")?;
+                Some(PublicFallback { after: Duration::from_secs(secs.parse()?), provider: Arc::new(provider) })
+            }
+            Err(_) => None,
+        };
+        Ok(SendMode::Private { relay, fallback })
+    }
+}
+
+/// A tx signed locally but not sent yet, so its hash and nonce can be recorded first
+#[derive(Debug, Clone)]
+pub struct SignedTx {
+    pub tx_hash: H256,
+    pub nonce: U256,
+    pub raw: Bytes,
+}
+
+/// Fills and signs `tx` with the client's wallet without sending it
+pub async fn sign_tx<M: Middleware + 'static, S: Signer + 'static>(
+    client: &SignerMiddleware<M, S>,
+    tx: impl Into<TypedTransaction>,
+) -> Result<SignedTx> {
+    let mut tx = tx.into();
+    client.fill_transaction(&mut tx, None).await?;
+    let signature = client.signer().sign_transaction(&tx).await?;
+    Ok(SignedTx {
+        tx_hash: tx.hash(&signature),
+        nonce: tx.nonce().copied().context("Filled tx has no nonce")?,
+        raw: tx.rlp_signed(&signature),
+    })
+}
+
+/// Sends an already signed tx according to `mode`. Returns as soon as the tx is out;
+/// a public fallback runs in a background task, or right away when the relay refuses the tx.
+pub async fn broadcast_tx<M: Middleware + 'static>(client: &M, signed: &SignedTx, mode: &SendMode) -> Result<H256> {
+    let SendMode::Private { relay, fallback } = mode else {
+        client.send_raw_transaction(signed.raw.clone()).await?;
+        return Ok(signed.tx_hash);
+    };
+
+    let relayed = relay
+        .send_private_tx(&format!("0x{}", hex::encode(&signed.raw)))
+        .await;
+    match (relayed, fallback.clone()) {
+        (Ok(_), Some(fallback)) => {
+            tokio::spawn(public_fallback(fallback, signed.tx_hash, signed.raw.clone()));
+        }
+        (Ok(_), None) => {}
+        (Err(e), Some(fallback)) => {
+            println!("⚠️ {} failed for {:?} ({}), broadcasting publicly now", relay.name(), signed.tx_hash, e);
+            fallback.provider.send_raw_transaction(signed.raw.clone()).await?;
+        }
+        (Err(e), None) => return Err(e.into()),
+    }
+    Ok(signed.tx_hash)
+}
+
+/// Fills, signs and sends `tx` from the client's wallet according to `mode`
+pub async fn send_tx<M: Middleware + 'static, S: Signer + 'static>(
+    client: &SignerMiddleware<M, S>,
+    tx: impl Into<TypedTransaction>,
+    mode: &SendMode,
+) -> Result<H256> {
+    let signed = sign_tx(client, tx).await?;
+    broadcast_tx(client, &signed, mode).await
+}
+
+/// Broadcasts `raw` publicly unless `tx_hash` gets mined within the deadline. Only fires
+/// while the process is still alive, i.e. while the caller waits for receipts.
+async fn public_fallback(fallback: PublicFallback, tx_hash: H256, raw: Bytes) {
+    let start = Instant::now();
+    while start.elapsed() < fallback.after {
+        if let Ok(Some(_)) = fallback.provider.get_transaction_receipt(tx_hash).await {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+    println!("⚠️ {:?} not mined privately after {:?}, broadcasting publicly", tx_hash, fallback.after);
+    // The relay may have leaked it already, "already known" is fine
+    if let Err(e) = fallback.provider.send_raw_transaction(raw).await {
+        println!("⚠️ Public fallback for {:?} failed: {}", tx_hash, e);
+    }
+}
@@ -0,0 +1,157 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::{Serialize, de::DeserializeOwned};
+use std::{env, str::FromStr};
+
+use crate::bundle::types::{BundleError, BundleReceipt, BundleRequest, CallBundleTx, JsonRpcRequest, JsonRpcResponse};
+use crate::bundle::{BlockRazorRelay, BloxrouteRelay, Bep322Relay, Club48Relay};
+
+/// A builder/relay endpoint that accepts BSC bundles
+#[async_trait]
+pub trait BundleRelay: Send + Sync {
+    /// Short name used in logs and reports
+    fn name(&self) -> &str;
+
+    /// Submits the bundle for `bundle.block_number`
+    async fn send_bundle(&self, bundle: &BundleRequest) -> Result<BundleReceipt, BundleError>;
+
+    /// Whether the relay can simulate bundles through `eth_callBundle`
+    fn supports_call_bundle(&self) -> bool {
+        false
+    }
+
+    /// Simulates the bundle on top of the latest state without submitting it
+    async fn call_bundle(&self, _bundle: &BundleRequest) -> Result<Vec<CallBundleTx>, BundleError> {
+        Err(BundleError::Unsupported {
+            relay: self.name().to_string(),
+            method: "eth_callBundle".to_string(),
+        })
+    }
+}
+
+/// Relay backends we know how to talk to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RelayKind {
+    Bloxroute,
+    Club48,
+    BlockRazor,
+    Bep322,
+}
+
+impl FromStr for RelayKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "bloxroute" | "blxr" => Ok(RelayKind::Bloxroute),
+            "48club" | "club48" | "puissant" => Ok(RelayKind::Club48),
+            "blockrazor" => Ok(RelayKind::BlockRazor),
+            "bep322" | "generic" => Ok(RelayKind::Bep322),
+            other => anyhow::bail!("Unknown bundle relay: {}", other),
+        }
+    }
+}
+
+impl RelayKind {
+    /// Builds the relay from `.env` settings (auth headers, custom URLs)
+    pub fn build(self) -> Result<Box<dyn BundleRelay>> {
+        self.build_with_client(Client::new())
+    }
+
+    /// Same as `build`, sharing `client` (and so one connection pool) with other relays.
+    /// Each backend's `with_client` does the same for a relay built by hand.
+    pub fn build_with_client(self, client: Client) -> Result<Box<dyn BundleRelay>> {
+        dotenvy::dotenv().ok();
+        let relay: Box<dyn BundleRelay> = match self {
+            RelayKind::Bloxroute => {
+                let auth = env::var("BLOXROUTE_AUTH_HEADER")
+                    .context("Missing BLOXROUTE_AUTH_HEADER in environment (.env)")?;
+                Box::new(BloxrouteRelay::new(&auth).with_client(client))
+            }
+            RelayKind::Club48 => Box::new(Club48Relay::new(env::var("CLUB48_RPC_URL").ok()).with_client(client)),
+            RelayKind::BlockRazor => {
+                let auth = env::var("BLOCKRAZOR_AUTH_HEADER")
+                    .context("Missing BLOCKRAZOR_AUTH_HEADER in environment (.env)")?;
+                Box::new(BlockRazorRelay::new(&auth, env::var("BLOCKRAZOR_RPC_URL").ok()).with_client(client))
+            }
+            RelayKind::Bep322 => {
+                let url = env::var("BEP322_RELAY_URL")
+                    .context("Missing BEP322_RELAY_URL in environment (.env)")?;
+                Box::new(Bep322Relay::new(&url, env::var("BEP322_AUTH_HEADER").ok()).with_client(client))
+            }
+        };
+        Ok(relay)
+    }
+}
+
+/// Picks the relay named by `BUNDLE_RELAY` (defaults to bloXroute)
+pub fn relay_from_env() -> Result<Box<dyn BundleRelay>> {
+    dotenvy::dotenv().ok();
+    let kind: RelayKind = env::var("BUNDLE_RELAY")
+        .unwrap_or_else(|_| "bloxroute".to_string())
+        .parse()?;
+    kind.build()
+}
+
+/// Every relay listed in `BUNDLE_RELAYS` (comma separated), sharing one HTTP client.
+/// Falls back to the single `BUNDLE_RELAY` when unset; never returns an empty list.
+pub fn relays_from_env() -> Result<Vec<Box<dyn BundleRelay>>> {
+    dotenvy::dotenv().ok();
+    let Ok(list) = env::var("BUNDLE_RELAYS") else {
+        return Ok(vec![relay_from_env()?]);
+    };
+
+    let client = Client::new();
+    let relays = list
+        .split(',')
+        .filter(|name| !name.trim().is_empty())
+        .map(|name| name.parse::<RelayKind>()?.build_with_client(client.clone()))
+        .collect::<Result<Vec<_>>>()?;
+    if relays.is_empty() {
+        anyhow::bail!("BUNDLE_RELAYS is set but names no relay (unset it to use BUNDLE_RELAY)");
+    }
+    Ok(relays)
+}
+
+/// Posts a JSON-RPC call and decodes the typed result, turning relay errors into `BundleError`
+pub(crate) async fn post_json_rpc<P: Serialize + Sync, R: DeserializeOwned>(
+    client: &Client,
+    relay: &str,
+    url: &str,
+    auth: Option<&str>,
+    method: &str,
+    params: P,
+) -> Result<R, BundleError> {
+    let transport = |source| BundleError::Transport { relay: relay.to_string(), source };
+
+    let mut req = client
+        .post(url)
+        .header("Content-Type", "application/json")
+        .json(&JsonRpcRequest::new(method, params));
+    if let Some(auth) = auth {
+        req = req.header("Authorization", auth);
+    }
+
+    let res = req.send().await.map_err(transport)?;
+    let status = res.status().as_u16();
+    let text = res.text().await.map_err(transport)?;
+    let invalid = || BundleError::InvalidResponse {
+        relay: relay.to_string(),
+        status,
+        body: text.clone(),
+    };
+
+    // Some relays answer auth failures with a plain-text 401/403
+    let Ok(body) = serde_json::from_str::<JsonRpcResponse<R>>(&text) else {
+        if status == 401 || status == 403 {
+            return Err(BundleError::InvalidAuth { relay: relay.to_string(), message: text });
+        }
+        return Err(invalid());
+    };
+    match (body.result, body.error) {
+        (_, Some(error)) => Err(BundleError::from_rpc(relay, status, error)),
+        (Some(result), None) => Ok(result),
+        (None, None) => Err(invalid()),
+    }
+}
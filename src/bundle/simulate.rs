@@ -0,0 +1,337 @@
+use anyhow::{Context, Result};
+use ethers::abi::{self, ParamType, Token};
+use ethers::prelude::*;
+use ethers::providers::RpcError;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::utils::rlp::Rlp;
+use std::{collections::BTreeMap, process::Command, sync::Arc, time::Duration};
+
+use crate::bundle::relay::BundleRelay;
+use crate::bundle::types::BundleRequest;
+use crate::bundle::{BundleBuilder, SignedBundle};
+
+/// Selector of the standard `Error(string)` revert
+const ERROR_STRING_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+/// Selector of ERC20 `balanceOf(address)`
+const BALANCE_OF_SELECTOR: [u8; 4] = [0x70, 0xa0, 0x82, 0x31];
+
+/// A signed tx taken apart again: the tx, who signed it and its hash
+#[derive(Debug, Clone)]
+pub struct DecodedTx {
+    pub tx: TypedTransaction,
+    pub from: Address,
+    pub hash: H256,
+}
+
+/// Decodes a signed raw tx (hex) and recovers its sender
+pub fn decode_signed_tx(raw_tx: &str) -> Result<DecodedTx> {
+    let bytes = hex::decode(raw_tx.trim_start_matches("0x"))?;
+    let (mut tx, signature) = TypedTransaction::decode_signed(&Rlp::new(&bytes))
+        .context("Failed to decode signed tx")?;
+    let from = signature.recover(tx.sighash())?;
+    tx.set_from(from);
+    let hash = tx.hash(&signature);
+    Ok(DecodedTx { tx, from, hash })
+}
+
+/// Where to run the simulation
+pub enum SimulationBackend<'a> {
+    /// The relay's own `eth_callBundle`
+    CallBundle(&'a dyn BundleRelay),
+    /// A throwaway anvil fork of `fork_url`
+    Anvil { fork_url: String },
+}
+
+impl<'a> SimulationBackend<'a> {
+    /// Uses `eth_callBundle` when the relay has it, an anvil fork otherwise
+    pub fn for_relay(relay: &'a dyn BundleRelay, fork_url: &str) -> Self {
+        if relay.supports_call_bundle() {
+            SimulationBackend::CallBundle(relay)
+        } else {
+            SimulationBackend::Anvil { fork_url: fork_url.to_string() }
+        }
+    }
+}
+
+/// Simulation every signed bundle has to pass before it goes to a relay
+pub struct BundleSimulation<'a> {
+    pub backend: SimulationBackend<'a>,
+    pub tokens: Vec<Address>, // ERC20s whose balance changes get reported
+}
+
+impl BundleSimulation<'_> {
+    /// Simulates `signed` and refuses it if a tx would revert or a sender is short of funds
+    pub async fn check(&self, provider: Arc<Provider<Http>>, signed: &SignedBundle) -> Result<SimulationReport> {
+        let report = simulate_bundle(provider, &signed.txs, &self.tokens, &self.backend).await?;
+        report.print_summary();
+        report.ensure_submittable(&signed.reverting_tx_hashes)?;
+        Ok(report)
+    }
+}
+
+/// Rebuilds a stale bundle (nonces, gas) and simulates the new one too, so nothing is sent
+/// that wasn't simulated
+pub async fn rebuild_bundle(
+    builder: &BundleBuilder,
+    provider: Arc<Provider<Http>>,
+    simulation: Option<&BundleSimulation<'_>>,
+) -> Result<SignedBundle> {
+    println!("♻️ Bundle went stale, rebuilding nonces and gas");
+    let signed = builder.build().await?;
+    if let Some(simulation) = simulation {
+        simulation.check(provider, &signed).await?;
+    }
+    Ok(signed)
+}
+
+/// Outcome of one bundle tx
+#[derive(Debug, Clone)]
+pub struct TxSimulation {
+    pub tx_hash: H256,
+    pub from: Address,
+    pub gas_used: U256,
+    pub revert: Option<String>,
+}
+
+/// Outcome of the whole bundle, run in order
+#[derive(Debug, Clone, Default)]
+pub struct SimulationReport {
+    pub txs: Vec<TxSimulation>,
+    pub bnb_deltas: BTreeMap<Address, I256>,
+    /// Keyed by (token, holder). Only the anvil backend can observe these.
+    pub token_deltas: BTreeMap<(Address, Address), I256>,
+    /// Senders whose balance can't cover `value + gas` for their txs
+    pub underfunded: Vec<Address>,
+}
+
+impl SimulationReport {
+    /// Refuses the bundle if any tx outside `allowed_reverts` reverts or any sender is short of funds
+    pub fn ensure_submittable(&self, allowed_reverts: &[H256]) -> Result<()> {
+        if let Some(address) = self.underfunded.first() {
+            anyhow::bail!("Sender {:?} can't cover value + gas for its bundle txs", address);
+        }
+        if let Some(tx) = self
+            .txs
+            .iter()
+            .find(|tx| tx.revert.is_some() && !allowed_reverts.contains(&tx.tx_hash))
+        {
+            anyhow::bail!(
+                "Bundle tx {:?} would revert: {}",
+                tx.tx_hash,
+                tx.revert.as_deref().unwrap_or_default()
+            );
+        }
+        Ok(())
+    }
+
+    pub fn print_summary(&self) {
+        for (i, tx) in self.txs.iter().enumerate() {
+            match &tx.revert {
+                Some(reason) => println!("❌ #{} {:?} reverts: {}", i, tx.tx_hash, reason),
+                None => println!("✅ #{} {:?} gas used {}", i, tx.tx_hash, tx.gas_used),
+            }
+        }
+        for (address, delta) in &self.bnb_deltas {
+            println!("💰 {:?} BNB delta: {} wei", address, delta);
+        }
+        for ((token, holder), delta) in &self.token_deltas {
+            println!("🪙 {:?} token {:?} delta: {}", holder, token, delta);
+        }
+        for address in &self.underfunded {
+            println!("⚠️ {:?} can't cover value + gas", address);
+        }
+    }
+}
+
+/// Simulates the signed bundle in order without submitting it.
+/// `tokens` are the ERC20s whose balance changes should be reported.
+pub async fn simulate_bundle(
+    provider: Arc<Provider<Http>>,
+    signed_txs: &[String],
+    tokens: &[Address],
+    backend: &SimulationBackend<'_>,
+) -> Result<SimulationReport> {
+    let decoded = signed_txs
+        .iter()
+        .map(|tx| decode_signed_tx(tx))
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut report = match backend {
+        SimulationBackend::CallBundle(relay) => {
+            simulate_with_call_bundle(&provider, *relay, signed_txs, &decoded).await?
+        }
+        SimulationBackend::Anvil { fork_url } => {
+            simulate_with_anvil(fork_url, signed_txs, &decoded, tokens).await?
+        }
+    };
+    report.underfunded = underfunded_senders(&provider, &decoded).await?;
+    Ok(report)
+}
+
+/// Senders whose current balance is below the sum of `value + gas * gas_price` over their txs
+async fn underfunded_senders(provider: &Provider<Http>, decoded: &[DecodedTx]) -> Result<Vec<Address>> {
+    let mut required: BTreeMap<Address, U256> = BTreeMap::new();
+    for d in decoded {
+        let value = d.tx.value().copied().unwrap_or_default();
+        let gas = d.tx.gas().copied().unwrap_or_default();
+        let gas_price = d.tx.gas_price().unwrap_or_default();
+        *required.entry(d.from).or_default() += value + gas * gas_price;
+    }
+
+    let mut underfunded = Vec::new();
+    for (sender, needed) in required {
+        if provider.get_balance(sender, None).await? < needed {
+            underfunded.push(sender);
+        }
+    }
+    Ok(underfunded)
+}
+
+async fn simulate_with_call_bundle(
+    provider: &Provider<Http>,
+    relay: &dyn BundleRelay,
+    signed_txs: &[String],
+    decoded: &[DecodedTx],
+) -> Result<SimulationReport> {
+    let bundle = BundleRequest::new(signed_txs.to_vec(), provider.get_block_number().await?.as_u64() + 1);
+    let results = relay.call_bundle(&bundle).await?;
+    if results.len() != decoded.len() {
+        anyhow::bail!("eth_callBundle returned {} results for {} txs", results.len(), decoded.len());
+    }
+
+    let mut report = SimulationReport::default();
+    for (d, result) in decoded.iter().zip(results) {
+        let gas_used = U256::from(result.gas_used);
+        let value = d.tx.value().copied().unwrap_or_default();
+        let fee = gas_used * d.tx.gas_price().unwrap_or_default();
+
+        // Only native value moves are visible here, state diffs aren't returned
+        *report.bnb_deltas.entry(d.from).or_insert_with(I256::zero) -= I256::from_raw(value + fee);
+        if let Some(to) = d.tx.to_addr() {
+            *report.bnb_deltas.entry(*to).or_insert_with(I256::zero) += I256::from_raw(value);
+        }
+
+        report.txs.push(TxSimulation {
+            tx_hash: d.hash,
+            from: d.from,
+            gas_used,
+            revert: result.revert.or(result.error),
+        });
+    }
+    Ok(report)
+}
+
+async fn simulate_with_anvil(
+    fork_url: &str,
+    signed_txs: &[String],
+    decoded: &[DecodedTx],
+    tokens: &[Address],
+) -> Result<SimulationReport> {
+    // Anvil::spawn panics when the binary is missing, check first
+    Command::new("anvil")
+        .arg("--version")
+        .output()
+        .context("anvil not found on PATH, needed for local bundle simulation")?;
+    let anvil = ethers::utils::Anvil::new().fork(fork_url).spawn();
+    let fork = Provider::<Http>::try_from(anvil.endpoint())?.interval(Duration::from_millis(50));
+
+    let mut holders: Vec<Address> = Vec::new();
+    for d in decoded {
+        holders.push(d.from);
+        if let Some(to) = d.tx.to_addr() {
+            holders.push(*to);
+        }
+    }
+    holders.sort();
+    holders.dedup();
+
+    let bnb_before = native_balances(&fork, &holders).await?;
+    let tokens_before = token_balances(&fork, tokens, &holders).await?;
+
+    let mut report = SimulationReport::default();
+    for (raw, d) in signed_txs.iter().zip(decoded) {
+        // Previous bundle txs are already mined, so the call sees the right state
+        let mut revert = match fork.call(&d.tx, None).await {
+            Ok(_) => None,
+            Err(e) => Some(revert_reason(&e)),
+        };
+
+        let bytes = Bytes::from(hex::decode(raw.trim_start_matches("0x"))?);
+        let receipt = fork
+            .send_raw_transaction(bytes)
+            .await?
+            .await?
+            .context("Simulated tx was dropped by anvil")?;
+        if receipt.status == Some(U64::zero()) && revert.is_none() {
+            revert = Some("reverted".to_string());
+        }
+
+        report.txs.push(TxSimulation {
+            tx_hash: d.hash,
+            from: d.from,
+            gas_used: receipt.gas_used.unwrap_or_default(),
+            revert,
+        });
+    }
+
+    let bnb_after = native_balances(&fork, &holders).await?;
+    for (address, before) in bnb_before {
+        let after = bnb_after[&address];
+        report.bnb_deltas.insert(address, I256::from_raw(after) - I256::from_raw(before));
+    }
+    let tokens_after = token_balances(&fork, tokens, &holders).await?;
+    for (key, before) in tokens_before {
+        let after = tokens_after[&key];
+        report.token_deltas.insert(key, I256::from_raw(after) - I256::from_raw(before));
+    }
+    Ok(report)
+}
+
+async fn native_balances(provider: &Provider<Http>, holders: &[Address]) -> Result<BTreeMap<Address, U256>> {
+    let mut balances = BTreeMap::new();
+    for holder in holders {
+        balances.insert(*holder, provider.get_balance(*holder, None).await?);
+    }
+    Ok(balances)
+}
+
+/// `balanceOf` for every (token, holder) pair; a token that doesn't exist yet reads as zero
+async fn token_balances(
+    provider: &Provider<Http>,
+    tokens: &[Address],
+    holders: &[Address],
+) -> Result<BTreeMap<(Address, Address), U256>> {
+    let mut balances = BTreeMap::new();
+    for token in tokens {
+        for holder in holders {
+            let mut data = BALANCE_OF_SELECTOR.to_vec();
+            data.extend(abi::encode(&[Token::Address(*holder)]));
+            let call: TypedTransaction = TransactionRequest::new().to(*token).data(data).into();
+            let balance = match provider.call(&call, None).await {
+                Ok(out) if out.len() >= 32 => U256::from_big_endian(&out[..32]),
+                _ => U256::zero(),
+            };
+            balances.insert((*token, *holder), balance);
+        }
+    }
+    Ok(balances)
+}
+
+/// Best-effort human readable revert reason from an `eth_call` error
+fn revert_reason(err: &ProviderError) -> String {
+    let Some(rpc_err) = RpcError::as_error_response(err) else {
+        return err.to_string();
+    };
+    rpc_err
+        .as_revert_data()
+        .and_then(|data| {
+            if data.len() < 4 || data[..4] != ERROR_STRING_SELECTOR {
+                return None;
+            }
+            abi::decode(&[ParamType::String], &data[4..])
+                .ok()?
+                .pop()?
+                .into_string()
+        })
+        .unwrap_or_else(|| rpc_err.message.clone())
+}
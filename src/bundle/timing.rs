@@ -0,0 +1,125 @@
+use anyhow::{Context, Result};
+use ethers::prelude::*;
+use std::{sync::Arc, time::{Duration, Instant, SystemTime, UNIX_EPOCH}};
+
+use crate::bundle::relay::BundleRelay;
+use crate::bundle::fanout::{drop_failed_relays, send_to_all};
+use crate::bundle::{BundleBuilder, BundleSimulation, BundleStatus, RelayOutcome, SignedBundle, rebuild_bundle};
+
+/// How long each stage of one block's submission took
+#[derive(Debug, Clone)]
+pub struct SubmissionTiming {
+    pub parent_block: u64,
+    pub target_block: u64,
+    pub head_lag: Duration, // parent block timestamp → newHeads notification (second resolution)
+    pub offset_wait: Duration,
+    pub build: Duration, // zero when the previous bundle was still fresh
+    pub submit: Duration, // slowest relay answer
+    pub total: Duration,  // newHeads notification → last relay answer
+}
+
+impl SubmissionTiming {
+    pub fn print(&self) {
+        println!(
+            "⏱️ parent {} → target {} | head lag {:?} | offset {:?} | build {:?} | submit {:?} | total {:?}",
+            self.parent_block,
+            self.target_block,
+            self.head_lag,
+            self.offset_wait,
+            self.build,
+            self.submit,
+            self.total
+        );
+    }
+}
+
+/// Fires the bundle `offset` after each new parent block arrives over websocket,
+/// for up to `window` blocks, stopping as soon as the bundle lands. A relay that fails with
+/// a non-retryable error sits out the rest of the window.
+#[allow(clippy::too_many_arguments)]
+pub async fn submit_on_new_heads(
+    ws_url: &str,
+    relays: &[Box<dyn BundleRelay>],
+    builder: &BundleBuilder,
+    signed: SignedBundle,
+    provider: Arc<Provider<Http>>,
+    offset: Duration,
+    window: u64,
+    simulation: Option<&BundleSimulation<'_>>,
+) -> Result<(BundleStatus, Vec<SubmissionTiming>, Vec<RelayOutcome>)> {
+    let ws = Provider::<Ws>::connect(ws_url)
+        .await
+        .context("Failed to connect to websocket RPC")?;
+    let mut heads = ws.subscribe_blocks().await?;
+
+    let mut signed = signed;
+    let mut timings = Vec::new();
+    let mut outcomes = Vec::new();
+    let mut last_target: Option<u64> = None;
+    let mut last_block: Option<u64> = None;
+    let mut active: Vec<&dyn BundleRelay> = relays.iter().map(|relay| relay.as_ref()).collect();
+
+    while let Some(head) = heads.next().await {
+        let arrived = Instant::now();
+        let Some(parent) = head.number.map(|n| n.as_u64()) else {
+            continue;
+        };
+
+        let last_block = *last_block.get_or_insert(parent + window.max(1));
+
+        // The new head is the block the previous submission targeted
+        if let Some(target) = last_target.filter(|t| *t <= parent) {
+            let status = signed
+                .tracker(provider.clone(), target)?
+                .until_block(last_block)
+                .check_block(target)
+                .await?;
+            println!("{}", status);
+            if status.is_final() {
+                return Ok((status, timings, outcomes));
+            }
+        }
+        if parent >= last_block {
+            break;
+        }
+        if active.is_empty() {
+            println!("❌ No relay left to submit to, giving up on the window");
+            break;
+        }
+
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?;
+        let head_lag = now.saturating_sub(Duration::from_secs(head.timestamp.as_u64()));
+        tokio::time::sleep(offset.saturating_sub(arrived.elapsed())).await;
+        let offset_wait = arrived.elapsed();
+
+        let build_start = Instant::now();
+        if signed.is_stale(&provider).await? {
+            signed = rebuild_bundle(builder, provider.clone(), simulation).await?;
+        }
+        let build = build_start.elapsed();
+
+        let target = parent + 1;
+        let submit_start = Instant::now();
+        let sent = send_to_all(&active, &signed.request(target)).await;
+        let submit = submit_start.elapsed();
+        drop_failed_relays(&mut active, &sent);
+        outcomes.extend(sent);
+
+        let timing = SubmissionTiming {
+            parent_block: parent,
+            target_block: target,
+            head_lag,
+            offset_wait,
+            build,
+            submit,
+            total: arrived.elapsed(),
+        };
+        timing.print();
+        timings.push(timing);
+
+        last_target = Some(target);
+    }
+
+    let last_block = last_target.unwrap_or_default();
+    Ok((BundleStatus::Expired { last_block }, timings, outcomes))
+}
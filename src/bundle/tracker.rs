@@ -0,0 +1,175 @@
+use anyhow::Result;
+use ethers::prelude::*;
+use ethers::utils::keccak256;
+use futures::stream::{self, Stream};
+use std::{fmt, sync::Arc, time::Duration};
+
+/// Where a submitted bundle stands on chain
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BundleStatus {
+    /// `block` was mined without any of the bundle's txs, still watching
+    Pending { block: u64 },
+    /// Every required tx landed and succeeded
+    Included { block: u64 },
+    /// Only some required txs landed, so the bundle was not applied atomically
+    PartiallyIncluded { block: u64, included: Vec<H256>, missing: Vec<H256> },
+    /// A tx not allowed to revert landed with `status == 0`
+    Reverted { block: u64, tx_hash: H256 },
+    /// The watch window ended without the bundle
+    Expired { last_block: u64 },
+}
+
+impl BundleStatus {
+    /// Whether the tracker stops after this status
+    pub fn is_final(&self) -> bool {
+        !matches!(self, BundleStatus::Pending { .. })
+    }
+}
+
+impl fmt::Display for BundleStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BundleStatus::Pending { block } => write!(f, "⏳ Not in block {}", block),
+            BundleStatus::Included { block } => write!(f, "✅ Included in block {}", block),
+            BundleStatus::PartiallyIncluded { block, included, missing } => write!(
+                f,
+                "⚠️ Partially included by block {} ({} landed, {} missing)",
+                block,
+                included.len(),
+                missing.len()
+            ),
+            BundleStatus::Reverted { block, tx_hash } => {
+                write!(f, "❌ Tx {:?} reverted in block {}", tx_hash, block)
+            }
+            BundleStatus::Expired { last_block } => {
+                write!(f, "⌛ Bundle expired after block {}", last_block)
+            }
+        }
+    }
+}
+
+/// Hash of a signed raw transaction (hex, with or without `0x`)
+pub fn signed_tx_hash(raw_tx: &str) -> Result<H256> {
+    let bytes = hex::decode(raw_tx.trim_start_matches("0x"))?;
+    Ok(H256::from(keccak256(bytes)))
+}
+
+/// Watches blocks `target_block..=max_block` for a bundle's tx hashes
+#[derive(Clone)]
+pub struct BundleTracker {
+    provider: Arc<Provider<Http>>,
+    tx_hashes: Vec<H256>,
+    allowed_reverts: Vec<H256>,
+    target_block: u64,
+    max_block: u64,
+    poll_interval: Duration,
+}
+
+impl BundleTracker {
+    pub fn new(provider: Arc<Provider<Http>>, tx_hashes: Vec<H256>, target_block: u64) -> Self {
+        Self {
+            provider,
+            tx_hashes,
+            allowed_reverts: Vec::new(),
+            target_block,
+            max_block: target_block,
+            poll_interval: Duration::from_millis(500),
+        }
+    }
+
+    /// Tracker for the txs of a signed bundle, hashes computed from the payloads
+    pub fn from_signed_txs(
+        provider: Arc<Provider<Http>>,
+        signed_txs: &[String],
+        target_block: u64,
+    ) -> Result<Self> {
+        let tx_hashes = signed_txs
+            .iter()
+            .map(|tx| signed_tx_hash(tx))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self::new(provider, tx_hashes, target_block))
+    }
+
+    /// Keep watching up to and including `max_block`
+    pub fn until_block(mut self, max_block: u64) -> Self {
+        self.max_block = max_block.max(self.target_block);
+        self
+    }
+
+    /// Txs that may revert or be dropped by the builder without failing the bundle
+    pub fn allow_revert(mut self, tx_hashes: Vec<H256>) -> Self {
+        self.allowed_reverts = tx_hashes;
+        self
+    }
+
+    pub fn poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    pub fn tx_hashes(&self) -> &[H256] {
+        &self.tx_hashes
+    }
+
+    /// Checks the bundle's txs once `block` is mined
+    pub async fn check_block(&self, block: u64) -> Result<BundleStatus> {
+        while self.provider.get_block_number().await?.as_u64() < block {
+            tokio::time::sleep(self.poll_interval).await;
+        }
+
+        let mut included = Vec::new();
+        let mut missing = Vec::new();
+        let mut landed_in = block;
+        for hash in &self.tx_hashes {
+            let can_revert = self.allowed_reverts.contains(hash);
+            match self.provider.get_transaction_receipt(*hash).await? {
+                Some(receipt) => {
+                    let receipt_block = receipt.block_number.map(|b| b.as_u64()).unwrap_or(block);
+                    if receipt.status == Some(U64::zero()) && !can_revert {
+                        return Ok(BundleStatus::Reverted { block: receipt_block, tx_hash: *hash });
+                    }
+                    landed_in = receipt_block;
+                    included.push(*hash);
+                }
+                // Builders may drop an allowed-revert tx and still include the rest
+                None if can_revert => {}
+                None => missing.push(*hash),
+            }
+        }
+
+        Ok(if !included.is_empty() && missing.is_empty() {
+            BundleStatus::Included { block: landed_in }
+        } else if !included.is_empty() {
+            BundleStatus::PartiallyIncluded { block: landed_in, included, missing }
+        } else if block >= self.max_block {
+            BundleStatus::Expired { last_block: block }
+        } else {
+            BundleStatus::Pending { block }
+        })
+    }
+
+    /// Yields one status per watched block, ending after the first final status
+    pub fn into_stream(self) -> impl Stream<Item = Result<BundleStatus>> {
+        let start = self.target_block;
+        stream::try_unfold((self, Some(start)), |(tracker, next)| async move {
+            let Some(block) = next else {
+                return Ok(None);
+            };
+            let status = tracker.check_block(block).await?;
+            let next = if status.is_final() { None } else { Some(block + 1) };
+            Ok(Some((status, (tracker, next))))
+        })
+    }
+
+    /// Drives the stream to its final status
+    pub async fn wait(self) -> Result<BundleStatus> {
+        let mut block = self.target_block;
+        loop {
+            let status = self.check_block(block).await?;
+            if status.is_final() {
+                return Ok(status);
+            }
+            block += 1;
+        }
+    }
+}
@@ -0,0 +1,224 @@
+use ethers::types::H256;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Which MEV builders a relay should forward the bundle to (bloXroute `mev_builders`)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BuilderSelection {
+    #[default]
+    All,
+    Only(Vec<String>),
+}
+
+impl BuilderSelection {
+    /// bloXroute's `{ "<builder>": "" }` map
+    pub fn to_mev_builders(&self) -> BTreeMap<String, String> {
+        match self {
+            BuilderSelection::All => BTreeMap::from([("all".to_string(), String::new())]),
+            BuilderSelection::Only(names) => names.iter().map(|n| (n.clone(), String::new())).collect(),
+        }
+    }
+}
+
+/// A bundle of signed transactions, independent of which relay receives it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleRequest {
+    pub txs: Vec<String>,  // hex strings of signed txs, executed in order
+    pub block_number: u64, // block the bundle targets
+    #[serde(default)]
+    pub max_block_number: Option<u64>, // last block the bundle stays valid for, if the relay supports it
+    #[serde(default)]
+    pub reverting_tx_hashes: Vec<H256>, // txs allowed to revert without dropping the bundle
+    #[serde(default)]
+    pub builders: BuilderSelection,
+}
+
+impl BundleRequest {
+    pub fn new(txs: Vec<String>, block_number: u64) -> Self {
+        Self {
+            txs,
+            block_number,
+            max_block_number: None,
+            reverting_tx_hashes: Vec::new(),
+            builders: BuilderSelection::All,
+        }
+    }
+
+    /// `max_block_number` or, when unset, the target block itself
+    pub fn last_block(&self) -> u64 {
+        self.max_block_number.unwrap_or(self.block_number)
+    }
+}
+
+/// What a relay handed back after accepting a bundle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleReceipt {
+    pub relay: String,
+    pub bundle_hash: Option<String>,
+    pub block_number: u64, // block the bundle was submitted for
+}
+
+/// Per-tx outcome of an `eth_callBundle` simulation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallBundleTx {
+    pub tx_hash: String,
+    pub gas_used: u64,
+    #[serde(default)]
+    pub error: Option<String>,
+    #[serde(default)]
+    pub revert: Option<String>,
+}
+
+/// JSON-RPC 2.0 envelope sent to relays
+#[derive(Debug, Serialize)]
+pub struct JsonRpcRequest<'a, P> {
+    pub jsonrpc: &'static str,
+    pub method: &'a str,
+    pub params: P,
+    pub id: i64,
+}
+
+impl<'a, P> JsonRpcRequest<'a, P> {
+    pub fn new(method: &'a str, params: P) -> Self {
+        Self {
+            jsonrpc: "2.0",
+            method,
+            params,
+            id: chrono::Utc::now().timestamp(),
+        }
+    }
+}
+
+/// JSON-RPC 2.0 response from a relay, exactly one of `result`/`error` is set
+#[derive(Debug, Deserialize)]
+pub struct JsonRpcResponse<R> {
+    pub result: Option<R>,
+    pub error: Option<JsonRpcErrorBody>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct JsonRpcErrorBody {
+    pub code: i64,
+    pub message: String,
+    #[serde(default)]
+    pub data: Option<serde_json::Value>,
+}
+
+/// `blxr_submit_bundle` params
+#[derive(Debug, Serialize)]
+pub struct BlxrBundleParams {
+    pub transaction: Vec<String>,
+    pub blockchain_network: String,
+    pub block_number: String, // hex
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub reverting_hashes: Vec<H256>,
+    pub mev_builders: BTreeMap<String, String>,
+}
+
+/// `blxr_submit_bundle` result
+#[derive(Debug, Deserialize)]
+pub struct BlxrBundleResult {
+    #[serde(rename = "bundleHash")]
+    pub bundle_hash: String,
+}
+
+/// `eth_sendBundle` params as used by BEP-322 builders (48Club, BlockRazor, ...)
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EthSendBundleParams {
+    pub txs: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_number: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_block_number: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_timestamp: Option<u64>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub reverting_tx_hashes: Vec<H256>,
+}
+
+/// `eth_sendBundle` result, a bare hash or `{ "bundleHash": ... }` depending on the builder
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum EthSendBundleResult {
+    Hash(String),
+    Object {
+        #[serde(rename = "bundleHash")]
+        bundle_hash: String,
+    },
+}
+
+impl EthSendBundleResult {
+    pub fn into_hash(self) -> String {
+        match self {
+            EthSendBundleResult::Hash(hash) => hash,
+            EthSendBundleResult::Object { bundle_hash } => bundle_hash,
+        }
+    }
+}
+
+/// `eth_callBundle` params
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallBundleParams {
+    pub txs: Vec<String>,
+    pub block_number: String, // hex
+    pub state_block_number: String,
+}
+
+/// `eth_callBundle` result
+#[derive(Debug, Deserialize)]
+pub struct CallBundleResult {
+    pub results: Vec<CallBundleTx>,
+}
+
+/// Why a relay did not take a bundle
+#[derive(Debug, thiserror::Error)]
+pub enum BundleError {
+    #[error("{relay}: invalid or missing auth header ({message})")]
+    InvalidAuth { relay: String, message: String },
+    #[error("{relay}: target block is stale ({message})")]
+    StaleBlock { relay: String, message: String },
+    #[error("{relay}: rate limited")]
+    RateLimited { relay: String },
+    #[error("{relay}: bundle rejected with code {code}: {message}")]
+    Rejected { relay: String, code: i64, message: String },
+    #[error("{relay}: {method} is not supported")]
+    Unsupported { relay: String, method: String },
+    #[error("{relay}: request failed: {source}")]
+    Transport { relay: String, source: reqwest::Error },
+    #[error("{relay}: unexpected response (status {status}): {body}")]
+    InvalidResponse { relay: String, status: u16, body: String },
+}
+
+impl BundleError {
+    /// Maps a relay's JSON-RPC error (and HTTP status) onto a matchable variant. Auth failures
+    /// go by status or code only, messages like "unauthorized sender nonce" are not about the header.
+    pub fn from_rpc(relay: &str, status: u16, error: JsonRpcErrorBody) -> Self {
+        let relay = relay.to_string();
+        let lower = error.message.to_ascii_lowercase();
+        if matches!(status, 401 | 403) || matches!(error.code, 401 | 403) {
+            BundleError::InvalidAuth { relay, message: error.message }
+        } else if status == 429 || lower.contains("rate limit") || lower.contains("too many requests") {
+            BundleError::RateLimited { relay }
+        } else if lower.contains("block")
+            && ["stale", "past", "too low", "old", "expired", "already"].iter().any(|w| lower.contains(w))
+        {
+            BundleError::StaleBlock { relay, message: error.message }
+        } else {
+            BundleError::Rejected { relay, code: error.code, message: error.message }
+        }
+    }
+
+    /// Worth retrying on the next block with the same bundle; the submit loops give up on
+    /// a relay (or the whole window) after any other error
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            BundleError::StaleBlock { .. } | BundleError::RateLimited { .. } | BundleError::Transport { .. } => true,
+            // A relay having a bad moment, not a verdict on the bundle
+            BundleError::InvalidResponse { status, .. } => *status >= 500,
+            BundleError::InvalidAuth { .. } | BundleError::Rejected { .. } | BundleError::Unsupported { .. } => false,
+        }
+    }
+}
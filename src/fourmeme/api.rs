@@ -0,0 +1,210 @@
+use anyhow::{Context, Result};
+use ethers::prelude::*;
+use reqwest::{Client, multipart};
+use serde::de::DeserializeOwned;
+use std::{collections::HashMap, env, path::Path, sync::Mutex, time::{Duration, Instant}};
+
+use crate::fourmeme::types::{ApiResponse, CreateTokenData, CreateTokenRequest, LoginRequest, NonceRequest, VerifyInfo};
+use crate::wallet::WalletSigner;
+
+pub const FOUR_MEME_API_URL: &str = "https://four.meme/meme-api/v1";
+
+/// How long a `meme-web-access` token is reused before logging in again
+pub const DEFAULT_SESSION_TTL: Duration = Duration::from_secs(60 * 60);
+
+struct Session {
+    access_token: String,
+    expires_at: Instant,
+}
+
+/// four.meme REST API; keeps one access token per wallet so repeated calls skip the login
+pub struct FourMemeApi {
+    client: Client,
+    url: String,
+    session_ttl: Duration,
+    sessions: Mutex<HashMap<Address, Session>>,
+}
+
+impl Default for FourMemeApi {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FourMemeApi {
+    pub fn new() -> Self {
+        Self {
+            client: Client::new(),
+            url: FOUR_MEME_API_URL.to_string(),
+            session_ttl: DEFAULT_SESSION_TTL,
+            sessions: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// `FOUR_MEME_API_URL` overrides the public endpoint
+    pub fn from_env() -> Self {
+        dotenvy::dotenv().ok();
+        match env::var("FOUR_MEME_API_URL") {
+            Ok(url) => Self::new().with_url(&url),
+            Err(_) => Self::new(),
+        }
+    }
+
+    /// Points the client at another endpoint (e.g. a staging host or a local mock)
+    pub fn with_url(mut self, url: &str) -> Self {
+        self.url = url.trim_end_matches('/').to_string();
+        self
+    }
+
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    pub fn with_session_ttl(mut self, ttl: Duration) -> Self {
+        self.session_ttl = ttl;
+        self
+    }
+
+    fn endpoint(&self, path: &str) -> String {
+        format!("{}/{}", self.url, path)
+    }
+
+    /// Login nonce for `address`, to be signed as `You are sign in Meme <nonce>`
+    pub async fn nonce_generate(&self, address: Address) -> Result<String> {
+        let address = format!("{:?}", address);
+        let req = NonceRequest {
+            account_address: &address,
+            verify_type: "LOGIN",
+            network_code: "BSC",
+        };
+        let res = self.client.post(self.endpoint("private/user/nonce/generate")).json(&req).send().await?;
+        into_data(res, "nonce").await
+    }
+
+    /// Trades the signed nonce for a `meme-web-access` token
+    pub async fn login_dex(&self, address: Address, signature: &Signature) -> Result<String> {
+        let address = format!("{:?}", address);
+        let req = LoginRequest {
+            region: "WEB",
+            lang_type: "EN",
+            login_ip: "",
+            invite_code: "",
+            verify_info: VerifyInfo {
+                address: &address,
+                network_code: "BSC",
+                signature: signature.to_string(),
+                verify_type: "LOGIN",
+            },
+            wallet_name: "MetaMask",
+        };
+        let res = self.client.post(self.endpoint("private/user/login/dex")).json(&req).send().await?;
+        into_data(res, "login").await
+    }
+
+    /// Access token of `wallet`, cached until it expires; logs in (nonce → sign → login) otherwise
+    pub async fn access_token(&self, wallet: &WalletSigner) -> Result<String> {
+        let address = wallet.address();
+        if let Some(session) = self.sessions.lock().unwrap().get(&address)
+            && session.expires_at > Instant::now()
+        {
+            return Ok(session.access_token.clone());
+        }
+
+        let nonce = self.nonce_generate(address).await?;
+        let signature = wallet.sign_message(format!("You are sign in Meme {}", nonce)).await?;
+        let access_token = self.login_dex(address, &signature).await?;
+        let session = Session { access_token: access_token.clone(), expires_at: Instant::now() + self.session_ttl };
+        self.sessions.lock().unwrap().insert(address, session);
+        Ok(access_token)
+    }
+
+    /// Drops the cached token of `address`, e.g. after the API rejected it
+    pub fn forget(&self, address: Address) {
+        self.sessions.lock().unwrap().remove(&address);
+    }
+
+    /// Runs `call` with `wallet`'s access token. A token the API rejects (revoked, or expired
+    /// before our TTL) is forgotten and `call` runs once more after a fresh login.
+    async fn with_session<T>(&self, wallet: &WalletSigner, call: impl AsyncFn(&str) -> Result<T>) -> Result<T> {
+        let access_token = self.access_token(wallet).await?;
+        match call(&access_token).await {
+            Err(e) if e.is::<SessionRejected>() => {
+                println!("🔑 four.meme rejected the session of {:?}, logging in again", wallet.address());
+                self.forget(wallet.address());
+                let access_token = self.access_token(wallet).await?;
+                call(&access_token).await
+            }
+            result => result,
+        }
+    }
+
+    /// `upload_image` as `wallet`, logging in as needed
+    pub async fn upload_image_as(&self, wallet: &WalletSigner, image_path: &Path) -> Result<String> {
+        self.with_session(wallet, async |access_token: &str| self.upload_image(access_token, image_path).await)
+            .await
+    }
+
+    /// `create_token` as `wallet`, logging in as needed
+    pub async fn create_token_as(&self, wallet: &WalletSigner, payload: &CreateTokenRequest) -> Result<CreateTokenData> {
+        self.with_session(wallet, async |access_token: &str| self.create_token(access_token, payload).await)
+            .await
+    }
+
+    /// Uploads a PNG and returns its hosted URL
+    pub async fn upload_image(&self, access_token: &str, image_path: &Path) -> Result<String> {
+        let file_bytes = tokio::fs::read(image_path)
+            .await
+            .with_context(|| format!("Failed to read image {}", image_path.display()))?;
+        let file_name = image_path.file_name().and_then(|n| n.to_str()).unwrap_or("image.png");
+        let form = multipart::Form::new().part(
+            "file",
+            multipart::Part::bytes(file_bytes).file_name(file_name.to_string()).mime_str("image/png")?,
+        );
+        let res = self
+            .client
+            .post(self.endpoint("private/token/upload"))
+            .header("meme-web-access", access_token)
+            .multipart(form)
+            .send()
+            .await?;
+        into_data(res, "upload").await
+    }
+
+    /// Registers the token and returns the `createToken` args signed by four.meme
+    pub async fn create_token(&self, access_token: &str, payload: &CreateTokenRequest) -> Result<CreateTokenData> {
+        let res = self
+            .client
+            .post(self.endpoint("private/token/create"))
+            .header("meme-web-access", access_token)
+            .json(payload)
+            .send()
+            .await?;
+        into_data(res, "create").await
+    }
+}
+
+/// four.meme refused the `meme-web-access` token, a fresh login fixes it
+#[derive(Debug, thiserror::Error)]
+#[error("four.meme {what} rejected the access token (HTTP {status}): {body}")]
+pub struct SessionRejected {
+    pub what: String,
+    pub status: u16,
+    pub body: String,
+}
+
+/// `data` of a successful response; a nonzero `code` or missing `data` is an error
+async fn into_data<T: DeserializeOwned>(res: reqwest::Response, what: &str) -> Result<T> {
+    let status = res.status();
+    let body = res.text().await?;
+    let code = serde_json::from_str::<ApiResponse<serde_json::Value>>(&body).map(|r| r.code).ok();
+    if matches!(status.as_u16(), 401 | 403) || matches!(code, Some(401 | 403)) {
+        return Err(SessionRejected { what: what.to_string(), status: status.as_u16(), body }.into());
+    }
+    let parsed: ApiResponse<T> = serde_json::from_str(&body)
+        .with_context(|| format!("four.meme {} returned HTTP {}: {}", what, status, body))?;
+    if parsed.code != 0 {
+        anyhow::bail!("four.meme {} failed (code {}): {}", what, parsed.code, parsed.message.unwrap_or_default());
+    }
+    parsed.data.with_context(|| format!("No data returned from four.meme {}", what))
+}
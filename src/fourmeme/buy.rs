@@ -1,7 +1,9 @@
 use ethers::prelude::*;
 use ethers::types::{U256, Address, transaction::eip2718::TypedTransaction};
 use std::sync::Arc;
-use anyhow::{Result, Context};
+use anyhow::Result;
+use crate::bundle::{SendMode, send_tx};
+use crate::wallet::WalletSigner;
 abigen!(
     TokenManager2,
     r#"[ 
@@ -16,6 +18,8 @@ abigen!(
     ERC20,
     r#"[ 
         function approve(address spender, uint256 amount) returns (bool)
+        function balanceOf(address owner) view returns (uint256)
+        function transfer(address to, uint256 amount) returns (bool)
     ]"#
 );
 
@@ -29,14 +33,14 @@ abigen!(
     "#
 );
 pub async fn get_buy_token_tx(
-    wallet: LocalWallet,
+    wallet: impl Into<WalletSigner>,
     provider: Arc<Provider<Http>>,
     token_manager_address: Address,
     token_address: Address,
     token_amount: f64,       // exact number of tokens
     max_bnb_ether: &str,     // max BNB to spend, e.g., "0.2"
 ) -> Result<TypedTransaction> {
-    let client = Arc::new(SignerMiddleware::new(provider.clone(), wallet));
+    let client = Arc::new(SignerMiddleware::new(provider.clone(), wallet.into()));
 
     // Connect contract
     let contract = TokenManager2::new(token_manager_address, client.clone());
@@ -55,13 +59,13 @@ pub async fn get_buy_token_tx(
 
 /// Returns a TypedTransaction for buying a token with fixed BNB (AMAP)
 pub async fn get_buy_amap_tx(
-    wallet: LocalWallet,
+    wallet: impl Into<WalletSigner>,
     provider: Arc<Provider<Http>>,
     token_manager_address: Address,
     token_address: Address,
     bnb_amount_ether: &str,  // BNB amount to spend
 ) -> Result<TypedTransaction> {
-    let client = Arc::new(SignerMiddleware::new(provider.clone(), wallet));
+    let client = Arc::new(SignerMiddleware::new(provider.clone(), wallet.into()));
 
     let contract = TokenManager2::new(token_manager_address, client.clone());
 
@@ -76,15 +80,16 @@ pub async fn get_buy_amap_tx(
 
 /// Approves TokenManager2 to spend your ERC20 tokens
 pub async fn approve_token(
-    wallet: LocalWallet,
+    wallet: impl Into<WalletSigner>,
     provider: Arc<Provider<Http>>,
     token_address: Address,
     manager_address: Address,
+    mode: &SendMode,
 ) -> Result<()> {
-    let client = Arc::new(SignerMiddleware::new(provider.clone(), wallet));
+    let client = Arc::new(SignerMiddleware::new(provider.clone(), wallet.into()));
     // After (fix)
     let token_contract = ERC20::new(token_address, client.clone());
     let approve_call = token_contract.approve(manager_address, U256::MAX);
-    approve_call.send().await?;
+    send_tx(&client, approve_call.tx, mode).await?;
     Ok(())
 }
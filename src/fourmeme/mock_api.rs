@@ -0,0 +1,143 @@
+use anyhow::Result;
+use ethers::prelude::*;
+use serde_json::{Value, json};
+use std::{
+    collections::{HashMap, HashSet},
+    net::SocketAddr,
+    sync::{Arc, Mutex, atomic::{AtomicUsize, Ordering}},
+};
+use tokio::io::BufReader;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+
+use crate::bundle::mock::{HttpRequest, read_http_request, write_http_json};
+use crate::fourmeme::api::FourMemeApi;
+
+/// `createArg` every mock create returns
+pub const MOCK_CREATE_ARG: &str = "0x1234";
+/// `signature` every mock create returns
+pub const MOCK_CREATE_SIGNATURE: &str = "0xabcd";
+
+#[derive(Default)]
+struct MockState {
+    nonces: Mutex<HashMap<Address, String>>,
+    tokens: Mutex<HashSet<String>>,
+    logins: AtomicUsize,
+    uploads: AtomicUsize,
+}
+
+/// Local stand-in for the four.meme REST API. Logins check the signed nonce, uploads and
+/// creates need a live `meme-web-access` token and answer 401 otherwise.
+pub struct MockFourMeme {
+    addr: SocketAddr,
+    state: Arc<MockState>,
+    server: JoinHandle<()>,
+}
+
+impl MockFourMeme {
+    /// Starts listening on a random local port
+    pub async fn start() -> Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let state = Arc::new(MockState::default());
+
+        let server_state = state.clone();
+        let server = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let state = server_state.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = handle_connection(stream, state).await {
+                        println!("⚠️ Mock four.meme connection error: {}", e);
+                    }
+                });
+            }
+        });
+
+        Ok(Self { addr, state, server })
+    }
+
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// A `FourMemeApi` pointed at this stand-in
+    pub fn api(&self) -> FourMemeApi {
+        FourMemeApi::new().with_url(&self.url())
+    }
+
+    /// Successful logins so far
+    pub fn logins(&self) -> usize {
+        self.state.logins.load(Ordering::SeqCst)
+    }
+
+    /// Images uploaded so far
+    pub fn uploads(&self) -> usize {
+        self.state.uploads.load(Ordering::SeqCst)
+    }
+
+    /// Revokes every access token handed out, as a server-side session expiry would
+    pub fn expire_sessions(&self) {
+        self.state.tokens.lock().unwrap().clear();
+    }
+}
+
+impl Drop for MockFourMeme {
+    fn drop(&mut self) {
+        self.server.abort();
+    }
+}
+
+async fn handle_connection(stream: TcpStream, state: Arc<MockState>) -> Result<()> {
+    let mut reader = BufReader::new(stream);
+    let request = read_http_request(&mut reader).await?;
+    let (status, response) = match handle_request(&state, &request) {
+        Ok(response) => response,
+        Err(e) => ("400 Bad Request", json!({ "code": 400, "message": e.to_string() })),
+    };
+    write_http_json(reader.get_mut(), status, &response).await
+}
+
+fn handle_request(state: &MockState, request: &HttpRequest) -> Result<(&'static str, Value)> {
+    let path = request.path.rsplit("/private/").next().unwrap_or_default();
+    let data = |data: Value| ("200 OK", json!({ "code": 0, "data": data }));
+    let error = |message: &str| ("200 OK", json!({ "code": 1, "message": message }));
+
+    match path {
+        "user/nonce/generate" => {
+            let body: Value = serde_json::from_slice(&request.body)?;
+            let address: Address = serde_json::from_value(body["accountAddress"].clone())?;
+            let nonce = format!("{:x}", H256::random());
+            state.nonces.lock().unwrap().insert(address, nonce.clone());
+            Ok(data(json!(nonce)))
+        }
+        "user/login/dex" => {
+            let body: Value = serde_json::from_slice(&request.body)?;
+            let info = &body["verify_info"];
+            let address: Address = serde_json::from_value(info["address"].clone())?;
+            let signature: Signature = info["signature"].as_str().unwrap_or_default().parse()?;
+            let Some(nonce) = state.nonces.lock().unwrap().remove(&address) else {
+                return Ok(error("no nonce for this address"));
+            };
+            if signature.verify(format!("You are sign in Meme {}", nonce), address).is_err() {
+                return Ok(error("signature does not match the nonce"));
+            }
+            let access_token = format!("{:x}", H256::random());
+            state.tokens.lock().unwrap().insert(access_token.clone());
+            state.logins.fetch_add(1, Ordering::SeqCst);
+            Ok(data(json!(access_token)))
+        }
+        "token/upload" | "token/create" => {
+            let access_token = request.header("meme-web-access").unwrap_or_default();
+            if !state.tokens.lock().unwrap().contains(access_token) {
+                return Ok(("401 Unauthorized", json!({ "code": 401, "message": "login expired" })));
+            }
+            if path == "token/upload" {
+                let n = state.uploads.fetch_add(1, Ordering::SeqCst);
+                return Ok(data(json!(format!("https://static.four.meme/mock/{}.png", n))));
+            }
+            let _: Value = serde_json::from_slice(&request.body)?;
+            Ok(data(json!({ "createArg": MOCK_CREATE_ARG, "signature": MOCK_CREATE_SIGNATURE })))
+        }
+        _ => Ok(("404 Not Found", json!({ "code": 404, "message": "no such endpoint" }))),
+    }
+}
@@ -1,10 +1,16 @@
+pub mod api;
 pub mod create;
 pub mod types;
 pub mod buy;
 pub mod sell;
+#[cfg(any(test, feature = "mock"))]
+pub mod mock_api;
 
 
+pub use api::*;
 pub use create::*;
 pub use types::*;
 pub use buy::*;
-pub use sell::*;
\ No newline at end of file
+pub use sell::*;
+#[cfg(any(test, feature = "mock"))]
+pub use mock_api::*;
\ No newline at end of file
@@ -2,35 +2,10 @@ use ethers::prelude::*;
 use ethers::types::{U256, Address, transaction::eip2718::TypedTransaction};
 use std::sync::Arc;
 use anyhow::Result;
+use crate::fourmeme::buy::TokenManager2;
 
-abigen!(
-    TokenManager2,
-    r#"[ 
-        function buyToken(address token, uint256 amount, uint256 maxFunds) payable
-        function buyTokenAMAP(address token, uint256 funds, uint256 minAmount) payable
-        function sellToken(address token, uint256 amount)
-        function sellTokenAMAP(address token, uint256 amount, uint256 minBNB)
-    ]"#
-);
-
-abigen!(
-    ERC20,
-    r#"[ 
-        function approve(address spender, uint256 amount) returns (bool)
-    ]"#
-);
-
-abigen!(
-    TokenManagerHelper3,
-    r#"
-    [
-        function tryBuy(address token, uint256 amount, uint256 funds) view returns (address tokenManager, address quote, uint256 estimatedAmount, uint256 estimatedCost, uint256 estimatedFee, uint256 amountMsgValue, uint256 amountApproval, uint256 amountFunds)
-        function trySell(address token, uint256 amount) view returns (address tokenManager, address quote, uint256 funds, uint256 fee)
-    ]
-    "#
-);
-pub async fn get_sell_token_tx<M: Middleware>(
-    client: Arc<SignerMiddleware<M, LocalWallet>>,
+pub async fn get_sell_token_tx<M: Middleware, S: Signer>(
+    client: Arc<SignerMiddleware<M, S>>,
     token_manager_address: Address,
     token_address: Address,      // ✅ token contract address
     sell_amount_tokens: f64,     // ✅ human-readable token amount (e.g. 25.0)
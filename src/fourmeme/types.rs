@@ -1,3 +1,4 @@
+use ethers::types::Bytes;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize)]
@@ -21,13 +22,16 @@ pub struct LoginRequest<'a> {
     pub wallet_name: &'a str,
 }
 
+/// Envelope of every four.meme API response, `code` 0 means success
 #[derive(Debug, Deserialize)]
-pub struct LoginResponse {
+pub struct ApiResponse<T> {
     pub code: i32,
-    pub data: Option<String>,
+    pub data: Option<T>,
     pub message: Option<String>,
 }
 
+pub type LoginResponse = ApiResponse<String>;
+
 #[derive(Debug, Serialize)]
 pub struct NonceRequest<'a> {
     #[serde(rename = "accountAddress")]
@@ -36,11 +40,7 @@ pub struct NonceRequest<'a> {
     pub network_code: &'a str,
 }
 
-#[derive(Debug, Deserialize)]
-pub struct NonceResponse {
-    pub code: i32,
-    pub data: String,
-}
+pub type NonceResponse = ApiResponse<String>;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CreateTokenRequest {
@@ -61,3 +61,11 @@ pub struct CreateTokenRequest {
     pub raised_amount: u64,
     pub symbol: String,
 }
+
+/// `createToken` arguments signed by four.meme
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateTokenData {
+    #[serde(rename = "createArg")]
+    pub create_arg: Bytes,
+    pub signature: Bytes,
+}
@@ -0,0 +1,143 @@
+use anyhow::{Context, Result};
+use ethers::abi::AbiEncode;
+use ethers::prelude::*;
+use ethers::utils::format_ether;
+use std::time::Duration;
+
+use crate::bundle::{SendMode, SignedTx, broadcast_tx, sign_tx};
+use crate::funding::{Transfer, TransferOutcome, TransferStatus, TransferSummary, receipt_status, shared_status, wait_for_receipt};
+
+abigen!(
+    Disperse,
+    r#"[
+        function disperseEther(address[] recipients, uint256[] values) payable
+    ]"#
+);
+
+/// Creation code of a minimal contract with Disperse's `disperseEther`: pays every recipient
+/// its value, reverts the whole call if any payment fails or the arrays differ in length,
+/// and refunds whatever is left to the caller. Hand-assembled (no solc), the annotated listing
+/// is in `disperse.evm` next to this file. Runtime is:
+///
+/// ```text
+/// selector != e63d38ed            → revert
+/// r = 4 + arg0, v = 4 + arg1
+/// len(r) != len(v)                → revert
+/// for i in 0..len(r): call(gas, r[i], v[i]) or revert
+/// selfbalance > 0: call(gas, caller, selfbalance) or revert
+/// ```
+pub const DISPERSE_BYTECODE: &str = "0x61007961000f6000396100796000f360003560e01c63e63d38ed1461001457600080fd5b60043560040160243560040181358135811461002f57600080fd5b60005b8181101561005d5760008080808460010160051b80880135908901355af11561007457600101610032565b471561007257600080808047335af115610074575b005b600080fd";
+
+/// Deploys `DISPERSE_BYTECODE` from the client's wallet, e.g. on a local anvil chain
+pub async fn deploy_disperse<M: Middleware + 'static, S: Signer + 'static>(
+    client: &SignerMiddleware<M, S>,
+) -> Result<Address> {
+    let code: Bytes = DISPERSE_BYTECODE.parse()?;
+    let tx = TransactionRequest::new().from(client.address()).data(code);
+    let receipt = client
+        .send_transaction(tx, None)
+        .await?
+        .await?
+        .context("Disperse deploy tx was dropped")?;
+    let address = receipt.contract_address.context("Deploy receipt has no contract address")?;
+    println!("📜 Deployed Disperse at {:?}", address);
+    Ok(address)
+}
+
+/// Gas assumed per recipient when the disperse call can't be estimated (e.g. treasury short on funds)
+pub const DISPERSE_GAS_PER_RECIPIENT: u64 = 40_000;
+/// Gas assumed for the call itself on top of the per-recipient cost
+pub const DISPERSE_GAS_BASE: u64 = 50_000;
+
+/// The `disperseEther` call for `transfers`, with `msg.value` exactly their sum
+pub fn disperse_tx(from: Address, disperse: Address, transfers: &[Transfer]) -> Result<TransactionRequest> {
+    let call = DisperseEtherCall {
+        recipients: transfers.iter().map(|t| t.to).collect(),
+        values: transfers.iter().map(|t| t.amount).collect(),
+    };
+    let total = call
+        .values
+        .iter()
+        .try_fold(U256::zero(), |sum, v| sum.checked_add(*v))
+        .context("Transfer amounts overflow")?;
+    Ok(TransactionRequest::new().from(from).to(disperse).value(total).data(call.encode()))
+}
+
+/// Gas limit of the disperse call, falling back to a per-recipient guess when estimation fails
+pub async fn estimate_disperse_gas<M: Middleware>(
+    client: &M,
+    from: Address,
+    disperse: Address,
+    transfers: &[Transfer],
+) -> Result<U256> {
+    let tx = disperse_tx(from, disperse, transfers)?.into();
+    Ok(match client.estimate_gas(&tx, None).await {
+        Ok(gas) => gas,
+        Err(e) => {
+            println!("⚠️ Could not estimate the disperse call ({}), assuming {} gas per wallet", e, DISPERSE_GAS_PER_RECIPIENT);
+            U256::from(DISPERSE_GAS_BASE + DISPERSE_GAS_PER_RECIPIENT * transfers.len() as u64)
+        }
+    })
+}
+
+/// Funds every transfer with one `disperseEther` call whose `msg.value` is exactly their sum.
+/// All transfers share the tx, so they're all funded or all failed; gas is split evenly.
+pub async fn disperse_bnb<M: Middleware + 'static, S: Signer + 'static>(
+    client: &SignerMiddleware<M, S>,
+    disperse: Address,
+    transfers: &[Transfer],
+    mode: &SendMode,
+    receipt_timeout: Duration,
+) -> Result<TransferSummary> {
+    disperse_bnb_with(client, disperse, transfers, mode, receipt_timeout, |_| Ok(())).await
+}
+
+/// `disperse_bnb`, calling `on_signed(tx)` after the call is signed and before it goes out.
+/// An error there stops before anything is broadcast.
+pub async fn disperse_bnb_with<M: Middleware + 'static, S: Signer + 'static>(
+    client: &SignerMiddleware<M, S>,
+    disperse: Address,
+    transfers: &[Transfer],
+    mode: &SendMode,
+    receipt_timeout: Duration,
+    on_signed: impl FnOnce(&SignedTx) -> Result<()>,
+) -> Result<TransferSummary> {
+    if transfers.is_empty() {
+        return Ok(TransferSummary::default());
+    }
+    // Without code there the whole value would just land on `disperse`
+    if client.get_code(disperse, None).await?.is_empty() {
+        anyhow::bail!("No contract at DISPERSE_ADDRESS {:?}", disperse);
+    }
+
+    let tx = disperse_tx(client.address(), disperse, transfers)?;
+    let total = tx.value.unwrap_or_default();
+
+    println!("📤 Dispersing {} BNB to {} wallets in one tx", format_ether(total), transfers.len());
+    let status = match sign_tx(client, tx).await {
+        Ok(signed) => {
+            on_signed(&signed)?;
+            let tx_hash = signed.tx_hash;
+            // The tx may have gone out despite the error, it is waited for and left pending if it never lands
+            if let Err(e) = broadcast_tx(client, &signed, mode).await {
+                println!("⚠️ Sending {:?} reported {}", tx_hash, e);
+            }
+            println!("⏳ Waiting for {:?}...", tx_hash);
+            match wait_for_receipt(client, tx_hash, receipt_timeout).await {
+                Ok(receipt) => receipt_status(tx_hash, receipt, receipt_timeout),
+                Err(e) => {
+                    println!("⚠️ Could not fetch the receipt of {:?}: {}", tx_hash, e);
+                    TransferStatus::Pending { tx_hash }
+                }
+            }
+        }
+        Err(e) => TransferStatus::Failed { tx_hash: None, error: e.to_string() },
+    };
+
+    let outcomes = transfers
+        .iter()
+        .enumerate()
+        .map(|(i, transfer)| TransferOutcome { transfer: *transfer, status: shared_status(&status, i, transfers.len()) })
+        .collect();
+    Ok(TransferSummary { outcomes })
+}
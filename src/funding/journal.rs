@@ -0,0 +1,420 @@
+use anyhow::{Context, Result};
+use chrono::Utc;
+use ethers::prelude::*;
+use ethers::utils::format_ether;
+use futures::future::{join_all, try_join_all};
+use serde::{Deserialize, Serialize};
+use std::{fs, path::{Path, PathBuf}, time::Duration};
+
+use crate::bundle::{SendMode, SignedTx, broadcast_tx};
+use crate::funding::{
+    FundingMode, FundingPlan, Transfer, TransferOutcome, TransferStatus, TransferSummary, disperse_bnb_with,
+    receipt_status, send_transfers_with, shared_status, top_up_amount, wait_for_receipt,
+};
+
+/// Where run journals live, one `<run id>.json` per run
+pub const RUNS_DIR: &str = "src/runs";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RunKind {
+    Distribute,
+    Sweep,
+    TokenSweep,
+}
+
+/// Where one transfer of a run got to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "lowercase")]
+pub enum JournalStatus {
+    Planned,
+    /// Signed and possibly sent. `nonce` tells a dropped tx from one that may still land,
+    /// `raw` lets a rerun broadcast the very same tx again. Older journals have neither.
+    Pending {
+        tx_hash: H256,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        nonce: Option<U256>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        raw: Option<Bytes>,
+    },
+    Done { tx_hash: H256, block: u64, gas_paid: U256 },
+    Failed { tx_hash: Option<H256>, error: String },
+}
+
+impl From<TransferStatus> for JournalStatus {
+    fn from(status: TransferStatus) -> Self {
+        match status {
+            TransferStatus::Funded { tx_hash, block, gas_paid } => JournalStatus::Done { tx_hash, block, gas_paid },
+            TransferStatus::Pending { tx_hash } => JournalStatus::Pending { tx_hash, nonce: None, raw: None },
+            TransferStatus::Failed { tx_hash, error } => JournalStatus::Failed { tx_hash, error },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub from: Address,
+    pub to: Address,
+    pub amount: U256, // planned; a sweep re-reads the balance when it actually sends
+    #[serde(flatten)]
+    pub status: JournalStatus,
+}
+
+impl JournalEntry {
+    pub fn planned(from: Address, to: Address, amount: U256) -> Self {
+        Self { from, to, amount, status: JournalStatus::Planned }
+    }
+
+    /// Not sent yet, or sent and failed
+    pub fn is_missing(&self) -> bool {
+        matches!(self.status, JournalStatus::Planned | JournalStatus::Failed { .. })
+    }
+
+    /// Hash of the tx this entry waits on, if it's pending
+    pub fn pending_hash(&self) -> Option<H256> {
+        match self.status {
+            JournalStatus::Pending { tx_hash, .. } => Some(tx_hash),
+            _ => None,
+        }
+    }
+}
+
+/// Planned and completed transfers of one distribute or sweep run, rewritten after every
+/// change so a rerun with the same id picks up exactly where the last one stopped
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunJournal {
+    pub run_id: String,
+    pub kind: RunKind,
+    pub mode: FundingMode,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<Address>, // token sweeps move this token, everything else moves BNB
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_up_target: Option<U256>, // top-up runs re-size what's still missing before sending
+    pub created_at: u64,
+    pub entries: Vec<JournalEntry>,
+}
+
+impl RunJournal {
+    pub fn path(run_id: &str) -> PathBuf {
+        Path::new(RUNS_DIR).join(format!("{}.json", run_id))
+    }
+
+    /// e.g. `distribute-20250101-120000`
+    pub fn new_run_id(kind: RunKind) -> String {
+        let kind = match kind {
+            RunKind::Distribute => "distribute",
+            RunKind::Sweep => "sweep",
+            RunKind::TokenSweep => "token-sweep",
+        };
+        format!("{}-{}", kind, Utc::now().format("%Y%m%d-%H%M%S"))
+    }
+
+    /// The journal of `run_id`, `None` if that run never started
+    pub fn load(run_id: &str) -> Result<Option<Self>> {
+        let path = Self::path(run_id);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let json = fs::read_to_string(&path).with_context(|| format!("Failed to read journal {}", path.display()))?;
+        let journal: Self = serde_json::from_str(&json).with_context(|| format!("Invalid journal {}", path.display()))?;
+        Ok(Some(journal))
+    }
+
+    /// Starts a new run, refusing to clobber an existing journal
+    pub fn create(
+        run_id: &str,
+        kind: RunKind,
+        mode: FundingMode,
+        token: Option<Address>,
+        entries: Vec<JournalEntry>,
+    ) -> Result<Self> {
+        if Self::path(run_id).exists() {
+            anyhow::bail!("Run {} already has a journal", run_id);
+        }
+        let journal = Self {
+            run_id: run_id.to_string(),
+            kind,
+            mode,
+            token,
+            top_up_target: None,
+            created_at: Utc::now().timestamp() as u64,
+            entries,
+        };
+        journal.save()?;
+        println!("📒 Started run {} ({} transfers)", run_id, journal.entries.len());
+        Ok(journal)
+    }
+
+    /// Distribution run for every transfer of `plan`
+    pub fn from_plan(run_id: &str, plan: &FundingPlan) -> Result<Self> {
+        let entries = plan
+            .transfers
+            .iter()
+            .map(|t| JournalEntry::planned(plan.treasury, t.to, t.amount))
+            .collect();
+        let mut journal = Self::create(run_id, RunKind::Distribute, plan.mode, None, entries)?;
+        journal.top_up_target = plan.top_up_target;
+        journal.save()?;
+        Ok(journal)
+    }
+
+    /// Written to a tmp file and renamed, a crash never leaves half a journal
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path(&self.run_id);
+        fs::create_dir_all(RUNS_DIR)?;
+        let tmp_path = path.with_extension("json.tmp");
+        fs::write(&tmp_path, serde_json::to_string_pretty(self)?)?;
+        fs::rename(&tmp_path, &path)?;
+        Ok(())
+    }
+
+    pub fn set_status(&mut self, index: usize, status: JournalStatus) -> Result<()> {
+        self.entries[index].status = status;
+        self.save()
+    }
+
+    /// Records `signed` as pending for every entry in `indices` before it is broadcast, so a crash
+    /// in between can't lead to a second payment. Entries sharing one tx keep the raw tx only once.
+    pub fn set_signed(&mut self, indices: &[usize], signed: &SignedTx) -> Result<()> {
+        for (k, i) in indices.iter().enumerate() {
+            self.entries[*i].status = JournalStatus::Pending {
+                tx_hash: signed.tx_hash,
+                nonce: Some(signed.nonce),
+                raw: (k == 0).then(|| signed.raw.clone()),
+            };
+        }
+        self.save()
+    }
+
+    /// Writes the outcome of a send back, keeping the nonce and raw tx of one still pending.
+    /// A failure with no tx never replaces a signed tx on record, that tx could still land.
+    pub fn set_outcome(&mut self, index: usize, status: TransferStatus) {
+        let entry = &mut self.entries[index];
+        match &status {
+            TransferStatus::Pending { tx_hash } if entry.pending_hash() == Some(*tx_hash) => return,
+            TransferStatus::Failed { tx_hash: None, .. } if entry.pending_hash().is_some() => return,
+            _ => {}
+        }
+        entry.status = status.into();
+    }
+
+    /// Re-reads the balance of every wallet still to be topped up to `target`: its amount becomes
+    /// what it lacks now, and wallets already there leave the run
+    pub async fn refresh_top_ups<M: Middleware + 'static>(&mut self, client: &M, target: U256) -> Result<()> {
+        let missing = self.missing();
+        let balances = try_join_all(missing.iter().map(|i| client.get_balance(self.entries[*i].to, None))).await?;
+        for (i, balance) in missing.into_iter().zip(balances).rev() {
+            match top_up_amount(target, balance) {
+                Some(amount) => self.entries[i].amount = amount,
+                None => {
+                    println!("⏭️ {:?} already holds {} BNB, dropping it from the run", self.entries[i].to, format_ether(balance));
+                    self.entries.remove(i);
+                }
+            }
+        }
+        self.save()
+    }
+
+    pub fn missing(&self) -> Vec<usize> {
+        (0..self.entries.len()).filter(|i| self.entries[*i].is_missing()).collect()
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.entries.iter().all(|e| matches!(e.status, JournalStatus::Done { .. }))
+    }
+
+    /// What the run moves; four.meme tokens have 18 decimals too, so amounts print the same way
+    pub fn unit(&self) -> &'static str {
+        if self.token.is_some() { "tokens" } else { "BNB" }
+    }
+
+    pub fn print(&self) {
+        let unit = self.unit();
+        println!("📒 Run {} ({:?})", self.run_id, self.kind);
+        for e in &self.entries {
+            let status = match &e.status {
+                JournalStatus::Planned => "📝 planned".to_string(),
+                JournalStatus::Pending { tx_hash, .. } => format!("⏳ pending | tx: {:?}", tx_hash),
+                JournalStatus::Done { tx_hash, gas_paid, .. } => {
+                    format!("✅ done | tx: {:?} | gas {} BNB", tx_hash, format_ether(*gas_paid))
+                }
+                JournalStatus::Failed { error, .. } => format!("❌ failed | {}", error),
+            };
+            println!("   {:?} → {:?} {} {} | {}", e.from, e.to, format_ether(e.amount), unit, status);
+        }
+        let summary = self.summary();
+        println!(
+            "📦 Done {} | not done {} | moved {} {} | gas {} BNB",
+            summary.funded(),
+            summary.failed(),
+            format_ether(summary.total_sent()),
+            unit,
+            format_ether(summary.total_gas())
+        );
+    }
+
+    /// Per-transfer result of the run so far; anything not done counts against `ensure_all_funded`
+    pub fn summary(&self) -> TransferSummary {
+        let outcomes = self
+            .entries
+            .iter()
+            .map(|e| {
+                let status = match &e.status {
+                    JournalStatus::Done { tx_hash, block, gas_paid } => {
+                        TransferStatus::Funded { tx_hash: *tx_hash, block: *block, gas_paid: *gas_paid }
+                    }
+                    JournalStatus::Failed { tx_hash, error } => {
+                        TransferStatus::Failed { tx_hash: *tx_hash, error: error.clone() }
+                    }
+                    JournalStatus::Pending { tx_hash, .. } => TransferStatus::Pending { tx_hash: *tx_hash },
+                    JournalStatus::Planned => TransferStatus::Failed { tx_hash: None, error: "not sent".to_string() },
+                };
+                TransferOutcome { transfer: Transfer { to: e.to, amount: e.amount }, status }
+            })
+            .collect();
+        TransferSummary { outcomes }
+    }
+
+    /// Waits for every pending tx by hash. Mined ones become done or failed. One the node doesn't
+    /// know is broadcast again as is while its nonce is still open; it only goes back to planned
+    /// once the sender's mined nonce has moved past it with no receipt, i.e. it can never land.
+    pub async fn settle_pending<M: Middleware + 'static>(
+        &mut self,
+        client: &M,
+        mode: &SendMode,
+        receipt_timeout: Duration,
+    ) -> Result<()> {
+        // One disperse call covers several entries, the raw tx is kept on the first of them
+        let mut pending: Vec<(H256, Address, Option<U256>, Option<Bytes>)> = Vec::new();
+        for e in &self.entries {
+            let JournalStatus::Pending { tx_hash, nonce, raw } = &e.status else { continue };
+            match pending.iter_mut().find(|(hash, ..)| hash == tx_hash) {
+                Some(known) => {
+                    known.2 = known.2.or(*nonce);
+                    known.3 = known.3.take().or_else(|| raw.clone());
+                }
+                None => pending.push((*tx_hash, e.from, *nonce, raw.clone())),
+            }
+        }
+        if pending.is_empty() {
+            return Ok(());
+        }
+
+        println!("⏳ Checking {} pending txs from the last attempt...", pending.len());
+        let settled = join_all(pending.into_iter().map(|(tx_hash, from, nonce, raw)| async move {
+            let still_pending = JournalStatus::Pending { tx_hash, nonce, raw: raw.clone() };
+            let Some(nonce) = nonce else {
+                // No nonce on record: a missing tx might still land, never send it again
+                return match wait_for_receipt(client, tx_hash, receipt_timeout).await {
+                    Ok(Some(receipt)) => (tx_hash, receipt_status(tx_hash, Some(receipt), receipt_timeout).into()),
+                    _ => (tx_hash, still_pending),
+                };
+            };
+
+            // Lost before or after the broadcast: the same bytes can still only land once
+            if let Some(raw) = &raw
+                && let Ok(None) = client.get_transaction(tx_hash).await
+                && let Ok(mined) = client.get_transaction_count(from, None).await
+                && mined <= nonce
+            {
+                println!("♻️ {:?} is unknown to the node, broadcasting it again", tx_hash);
+                let signed = SignedTx { tx_hash, nonce, raw: raw.clone() };
+                if let Err(e) = broadcast_tx(client, &signed, mode).await {
+                    println!("⚠️ Rebroadcast of {:?} failed: {}", tx_hash, e);
+                }
+            }
+
+            let status = match wait_for_receipt(client, tx_hash, receipt_timeout).await {
+                Ok(Some(receipt)) => receipt_status(tx_hash, Some(receipt), receipt_timeout).into(),
+                Ok(None) => match client.get_transaction_count(from, None).await {
+                    // The nonce went to another tx, unless this one got mined just now
+                    Ok(mined) if mined > nonce => match client.get_transaction_receipt(tx_hash).await {
+                        Ok(Some(receipt)) => receipt_status(tx_hash, Some(receipt), receipt_timeout).into(),
+                        Ok(None) => {
+                            println!("♻️ {:?} was replaced and can't land anymore, sending it again", tx_hash);
+                            JournalStatus::Planned
+                        }
+                        Err(_) => still_pending,
+                    },
+                    _ => still_pending,
+                },
+                Err(_) => still_pending,
+            };
+            (tx_hash, status)
+        }))
+        .await;
+
+        for (tx_hash, status) in settled {
+            let shared: Vec<usize> = (0..self.entries.len())
+                .filter(|i| self.entries[*i].pending_hash() == Some(tx_hash))
+                .collect();
+            for (k, i) in shared.iter().enumerate() {
+                self.entries[*i].status = match &status {
+                    // Each entry keeps its share of the gas
+                    JournalStatus::Done { tx_hash, block, gas_paid } => {
+                        let funded = TransferStatus::Funded { tx_hash: *tx_hash, block: *block, gas_paid: *gas_paid };
+                        shared_status(&funded, k, shared.len()).into()
+                    }
+                    // Still waiting, every entry keeps what it had on record
+                    JournalStatus::Pending { .. } => continue,
+                    other => other.clone(),
+                };
+            }
+        }
+        self.save()
+    }
+}
+
+/// Runs (or resumes) a distribution: settles what the last attempt left pending, then sends
+/// only the transfers that are still missing, journaling each tx before it goes out
+pub async fn run_distribution<M: Middleware + 'static, S: Signer + 'static>(
+    client: &SignerMiddleware<M, S>,
+    journal: &mut RunJournal,
+    mode: &SendMode,
+    receipt_timeout: Duration,
+) -> Result<TransferSummary> {
+    if journal.entries.iter().any(|e| e.from != client.address()) {
+        anyhow::bail!("Run {} was planned for another treasury than {:?}", journal.run_id, client.address());
+    }
+    journal.settle_pending(client, mode, receipt_timeout).await?;
+    // Balances may have moved since the plan was made
+    if let Some(target) = journal.top_up_target {
+        journal.refresh_top_ups(client, target).await?;
+    }
+
+    let missing = journal.missing();
+    if missing.is_empty() {
+        println!("✅ Run {} has nothing left to send", journal.run_id);
+        return Ok(journal.summary());
+    }
+    let transfers: Vec<Transfer> = missing
+        .iter()
+        .map(|i| Transfer { to: journal.entries[*i].to, amount: journal.entries[*i].amount })
+        .collect();
+    println!("📒 Run {}: sending {} of {} transfers", journal.run_id, transfers.len(), journal.entries.len());
+
+    // Same coverage check as a fresh plan, for what's left
+    let plan = FundingPlan::new(client, transfers.clone(), journal.mode).await?;
+    plan.ensure_covered()?;
+
+    let result = match journal.mode {
+        FundingMode::Transfers => {
+            send_transfers_with(client, &transfers, mode, receipt_timeout, |k, signed| {
+                journal.set_signed(&[missing[k]], signed)
+            })
+            .await?
+        }
+        FundingMode::Disperse(disperse) => {
+            disperse_bnb_with(client, disperse, &transfers, mode, receipt_timeout, |signed| {
+                journal.set_signed(&missing, signed)
+            })
+            .await?
+        }
+    };
+
+    for (i, outcome) in missing.iter().zip(result.outcomes) {
+        journal.set_outcome(*i, outcome.status);
+    }
+    journal.save()?;
+    Ok(journal.summary())
+}
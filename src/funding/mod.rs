@@ -0,0 +1,12 @@
+pub mod transfer;
+pub mod disperse;
+pub mod plan;
+pub mod journal;
+pub mod sweep;
+
+
+pub use transfer::*;
+pub use disperse::*;
+pub use plan::*;
+pub use journal::*;
+pub use sweep::*;
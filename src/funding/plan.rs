@@ -0,0 +1,213 @@
+use anyhow::{Context, Result};
+use ethers::prelude::*;
+use ethers::utils::format_ether;
+use futures::future::try_join_all;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::{env, fs, path::Path, time::{SystemTime, UNIX_EPOCH}};
+
+use crate::funding::{FundingMode, TRANSFER_GAS, Transfer, estimate_disperse_gas};
+
+/// How much each wallet gets
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FundingAmount {
+    /// A fresh random amount in `[min, max]` BNB, whatever the wallet already holds
+    Random { min: f64, max: f64 },
+    /// Only the difference up to `target` wei; wallets already there are skipped
+    TopUp { target: U256 },
+}
+
+impl FundingAmount {
+    /// Enough for a wallet to buy with `buy` and still pay its own gas out of `gas_reserve`
+    pub fn top_up_for_buy(buy: U256, gas_reserve: U256) -> Self {
+        FundingAmount::TopUp { target: buy + gas_reserve }
+    }
+
+    /// `FUNDING_AMOUNT` = random (default, `MIN_BNB`..`MAX_BNB`) | topup (`TOPUP_TARGET_BNB`,
+    /// or `TOPUP_BUY_BNB` plus `TOPUP_GAS_RESERVE_BNB`)
+    pub fn from_env() -> Result<Self> {
+        dotenvy::dotenv().ok();
+        let bnb = |key: &str| -> Result<Option<U256>> {
+            env::var(key).ok().map(|v| Ok(ethers::utils::parse_ether(v.trim())?)).transpose()
+        };
+        let kind = env::var("FUNDING_AMOUNT").unwrap_or_else(|_| "random".to_string());
+        match kind.trim().to_ascii_lowercase().as_str() {
+            "random" => Ok(FundingAmount::Random {
+                min: env::var("MIN_BNB").context("Missing MIN_BNB in environment (.env)")?.parse()?,
+                max: env::var("MAX_BNB").context("Missing MAX_BNB in environment (.env)")?.parse()?,
+            }),
+            "topup" => match (bnb("TOPUP_TARGET_BNB")?, bnb("TOPUP_BUY_BNB")?) {
+                (Some(target), _) => Ok(FundingAmount::TopUp { target }),
+                (None, Some(buy)) => Ok(Self::top_up_for_buy(buy, bnb("TOPUP_GAS_RESERVE_BNB")?.unwrap_or_default())),
+                (None, None) => anyhow::bail!("FUNDING_AMOUNT=topup needs TOPUP_TARGET_BNB or TOPUP_BUY_BNB"),
+            },
+            other => anyhow::bail!("Unknown FUNDING_AMOUNT: {}", other),
+        }
+    }
+}
+
+/// What a wallet holding `balance` still lacks to reach `target`, `None` if it's already there
+pub fn top_up_amount(target: U256, balance: U256) -> Option<U256> {
+    target.checked_sub(balance).filter(|amount| !amount.is_zero())
+}
+
+/// One transfer per wallet sized by `amount`. Top-ups read every balance first and leave
+/// out wallets that already hold the target.
+pub async fn plan_transfers<M: Middleware + 'static>(
+    client: &M,
+    wallets: &[Address],
+    amount: &FundingAmount,
+) -> Result<Vec<Transfer>> {
+    match *amount {
+        FundingAmount::Random { min, max } => {
+            let mut rng = rand::thread_rng();
+            wallets
+                .iter()
+                .map(|to| {
+                    // Random amount between min and max
+                    let amount_bnb = rng.gen_range(min..=max);
+                    Ok(Transfer { to: *to, amount: ethers::utils::parse_ether(amount_bnb)? })
+                })
+                .collect()
+        }
+        FundingAmount::TopUp { target } => {
+            let balances = try_join_all(wallets.iter().map(|w| client.get_balance(*w, None))).await?;
+            let mut transfers = Vec::new();
+            for (to, balance) in wallets.iter().zip(balances) {
+                match top_up_amount(target, balance) {
+                    Some(amount) => transfers.push(Transfer { to: *to, amount }),
+                    None => println!("⏭️ {:?} already holds {} BNB, skipping", to, format_ether(balance)),
+                }
+            }
+            println!(
+                "🎯 Topping up {} of {} wallets to {} BNB",
+                transfers.len(),
+                wallets.len(),
+                format_ether(target)
+            );
+            Ok(transfers)
+        }
+    }
+}
+
+/// Every transfer of a funding run, worked out and costed before anything is sent
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FundingPlan {
+    pub created_at: u64,
+    pub chain_id: u64,
+    pub treasury: Address,
+    pub mode: FundingMode,
+    pub transfers: Vec<Transfer>,
+    pub gas_limit: U256, // all txs of the run together
+    pub gas_price: U256,
+    pub treasury_balance: U256, // when the plan was made
+    /// Set for top-ups: amounts are re-read against current balances before the plan runs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_up_target: Option<U256>,
+}
+
+/// Gas limit of all txs that fund `transfers` from `treasury`
+async fn plan_gas_limit<M: Middleware>(client: &M, treasury: Address, mode: FundingMode, transfers: &[Transfer]) -> Result<U256> {
+    Ok(match mode {
+        FundingMode::Transfers => U256::from(TRANSFER_GAS) * transfers.len(),
+        FundingMode::Disperse(disperse) => estimate_disperse_gas(client, treasury, disperse, transfers).await?,
+    })
+}
+
+impl FundingPlan {
+    /// Prices `transfers` from `client`'s wallet at the current gas price and balance
+    pub async fn new<M: Middleware + 'static, S: Signer + 'static>(
+        client: &SignerMiddleware<M, S>,
+        transfers: Vec<Transfer>,
+        mode: FundingMode,
+    ) -> Result<Self> {
+        let treasury = client.address();
+        let gas_limit = plan_gas_limit(client, treasury, mode, &transfers).await?;
+        Ok(Self {
+            created_at: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+            chain_id: client.get_chainid().await?.as_u64(),
+            treasury,
+            mode,
+            transfers,
+            gas_limit,
+            gas_price: client.get_gas_price().await?,
+            treasury_balance: client.get_balance(treasury, None).await?,
+            top_up_target: None,
+        })
+    }
+
+    pub fn total_amount(&self) -> U256 {
+        self.transfers.iter().fold(U256::zero(), |sum, t| sum + t.amount)
+    }
+
+    pub fn gas_cost(&self) -> U256 {
+        self.gas_limit * self.gas_price
+    }
+
+    pub fn total_cost(&self) -> U256 {
+        self.total_amount() + self.gas_cost()
+    }
+
+    /// Treasury balance left after the run, `None` if it can't cover the plan
+    pub fn remaining(&self) -> Option<U256> {
+        self.treasury_balance.checked_sub(self.total_cost())
+    }
+
+    pub fn ensure_covered(&self) -> Result<()> {
+        if self.remaining().is_none() {
+            anyhow::bail!(
+                "Treasury {:?} holds {} BNB but the plan needs {} BNB",
+                self.treasury,
+                format_ether(self.treasury_balance),
+                format_ether(self.total_cost())
+            );
+        }
+        Ok(())
+    }
+
+    pub fn print(&self) {
+        println!("📝 Funding plan: {} wallets via {:?} on chain {}", self.transfers.len(), self.mode, self.chain_id);
+        for (i, t) in self.transfers.iter().enumerate() {
+            println!("   {:>3}. {:?} ← {} BNB", i + 1, t.to, format_ether(t.amount));
+        }
+        println!("   amount    {} BNB", format_ether(self.total_amount()));
+        println!("   gas       {} BNB ({} gas at {} gwei)", format_ether(self.gas_cost()), self.gas_limit, ethers::utils::format_units(self.gas_price, "gwei").unwrap_or_default());
+        println!("   treasury  {} BNB", format_ether(self.treasury_balance));
+        match self.remaining() {
+            Some(left) => println!("   remaining {} BNB", format_ether(left)),
+            None => println!("   ❌ short by {} BNB", format_ether(self.total_cost() - self.treasury_balance)),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write plan {}", path.display()))?;
+        println!("💾 Saved funding plan to {}", path.display());
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let json = fs::read_to_string(path).with_context(|| format!("Failed to read plan {}", path.display()))?;
+        serde_json::from_str(&json).with_context(|| format!("Invalid plan {}", path.display()))
+    }
+
+    /// Re-prices the saved plan against the treasury's current balance and gas price.
+    /// Random amounts stay exactly as planned; top-ups are re-sized to what each wallet lacks now.
+    pub async fn refresh<M: Middleware + 'static, S: Signer + 'static>(&mut self, client: &SignerMiddleware<M, S>) -> Result<()> {
+        let chain_id = client.get_chainid().await?.as_u64();
+        if chain_id != self.chain_id {
+            anyhow::bail!("Plan was made for chain {}, connected to chain {}", self.chain_id, chain_id);
+        }
+        if client.address() != self.treasury {
+            anyhow::bail!("Plan was made for treasury {:?}, signer is {:?}", self.treasury, client.address());
+        }
+        if let Some(target) = self.top_up_target {
+            let wallets: Vec<Address> = self.transfers.iter().map(|t| t.to).collect();
+            self.transfers = plan_transfers(client, &wallets, &FundingAmount::TopUp { target }).await?;
+            self.gas_limit = plan_gas_limit(client, self.treasury, self.mode, &self.transfers).await?;
+        }
+        self.gas_price = client.get_gas_price().await?;
+        self.treasury_balance = client.get_balance(self.treasury, None).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,85 @@
+use anyhow::Result;
+use ethers::prelude::*;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use std::{env, str::FromStr};
+
+use crate::funding::TRANSFER_GAS;
+
+/// Tx type used to sweep a wallet empty
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SweepTxType {
+    #[default]
+    Legacy,
+    Eip1559,
+}
+
+impl FromStr for SweepTxType {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "legacy" => Ok(SweepTxType::Legacy),
+            "eip1559" | "1559" => Ok(SweepTxType::Eip1559),
+            other => anyhow::bail!("Unknown sweep tx type: {}", other),
+        }
+    }
+}
+
+impl SweepTxType {
+    /// `SWEEP_TX_TYPE` = legacy (default) | eip1559
+    pub fn from_env() -> Result<Self> {
+        dotenvy::dotenv().ok();
+        env::var("SWEEP_TX_TYPE").unwrap_or_else(|_| "legacy".to_string()).parse()
+    }
+}
+
+/// Gas limit, price and type of one sweep, fixed before the tx is built so its fee is exact
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SweepGas {
+    pub tx_type: SweepTxType,
+    pub gas_limit: U256,
+    pub gas_price: U256,
+}
+
+impl SweepGas {
+    /// Current gas price; the limit is a plain transfer's, estimated instead if `to` is a contract
+    pub async fn fetch<M: Middleware + 'static>(client: &M, tx_type: SweepTxType, from: Address, to: Address) -> Result<Self> {
+        let gas_limit = if client.get_code(to, None).await?.is_empty() {
+            U256::from(TRANSFER_GAS)
+        } else {
+            // A contract may use less than the estimate, the difference stays behind as dust
+            let tx = TransactionRequest::pay(to, 1u64).from(from).into();
+            client.estimate_gas(&tx, None).await?
+        };
+        Ok(Self { tx_type, gas_limit, gas_price: client.get_gas_price().await? })
+    }
+
+    /// Most the tx can cost, and exactly what it costs when all of `gas_limit` is used
+    pub fn fee(&self) -> U256 {
+        self.gas_limit * self.gas_price
+    }
+
+    /// Moves `balance` minus the fee from `from` to `to`, `None` if the balance can't cover the fee.
+    /// A 1559 tx gets `max_fee == max_priority_fee == gas_price`: whatever the base fee, the
+    /// effective price is then exactly `gas_price`, so the wallet ends at zero like with a legacy tx.
+    pub fn sweep_tx(&self, from: Address, to: Address, balance: U256, chain_id: u64) -> Option<TypedTransaction> {
+        let amount = balance.checked_sub(self.fee()).filter(|amount| !amount.is_zero())?;
+        Some(match self.tx_type {
+            SweepTxType::Legacy => TransactionRequest::pay(to, amount)
+                .from(from)
+                .gas(self.gas_limit)
+                .gas_price(self.gas_price)
+                .chain_id(chain_id)
+                .into(),
+            SweepTxType::Eip1559 => Eip1559TransactionRequest::new()
+                .from(from)
+                .to(to)
+                .value(amount)
+                .gas(self.gas_limit)
+                .max_fee_per_gas(self.gas_price)
+                .max_priority_fee_per_gas(self.gas_price)
+                .chain_id(chain_id)
+                .into(),
+        })
+    }
+}
@@ -0,0 +1,273 @@
+use anyhow::{Context, Result};
+use ethers::prelude::*;
+use ethers::utils::format_ether;
+use futures::future::join_all;
+use serde::{Deserialize, Serialize};
+use std::{env, time::{Duration, Instant}};
+
+use crate::bundle::{SendMode, SignedTx, broadcast_tx, sign_tx};
+
+/// How long to wait for a funding tx to be mined before calling it failed
+pub const DEFAULT_RECEIPT_TIMEOUT: Duration = Duration::from_secs(120);
+/// Gas of a plain BNB transfer to an EOA
+pub const TRANSFER_GAS: u64 = 21_000;
+
+/// How the main wallet pays out a batch of transfers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FundingMode {
+    /// One 21k-gas transfer per wallet
+    #[default]
+    Transfers,
+    /// One `disperseEther` call to the contract at this address
+    Disperse(Address),
+}
+
+impl FundingMode {
+    /// `FUNDING_MODE` = transfers (default) | disperse (needs `DISPERSE_ADDRESS`)
+    pub fn from_env() -> Result<Self> {
+        dotenvy::dotenv().ok();
+        let mode = env::var("FUNDING_MODE").unwrap_or_else(|_| "transfers".to_string());
+        match mode.trim().to_ascii_lowercase().as_str() {
+            "transfers" => Ok(FundingMode::Transfers),
+            "disperse" => {
+                let address = env::var("DISPERSE_ADDRESS")
+                    .context("Missing DISPERSE_ADDRESS in environment (.env)")?;
+                Ok(FundingMode::Disperse(address.parse()?))
+            }
+            other => anyhow::bail!("Unknown FUNDING_MODE: {}", other),
+        }
+    }
+}
+
+/// One BNB transfer from the main wallet
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Transfer {
+    pub to: Address,
+    pub amount: U256,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransferStatus {
+    Funded { tx_hash: H256, block: u64, gas_paid: U256 },
+    /// Sent but not seen mined in time, it may still land
+    Pending { tx_hash: H256 },
+    Failed { tx_hash: Option<H256>, error: String },
+}
+
+#[derive(Debug, Clone)]
+pub struct TransferOutcome {
+    pub transfer: Transfer,
+    pub status: TransferStatus,
+}
+
+impl TransferOutcome {
+    pub fn is_funded(&self) -> bool {
+        matches!(self.status, TransferStatus::Funded { .. })
+    }
+}
+
+/// Per-wallet result of a funding run
+#[derive(Debug, Clone, Default)]
+pub struct TransferSummary {
+    pub outcomes: Vec<TransferOutcome>,
+}
+
+impl TransferSummary {
+    pub fn funded(&self) -> usize {
+        self.outcomes.iter().filter(|o| o.is_funded()).count()
+    }
+
+    pub fn failed(&self) -> usize {
+        self.outcomes.len() - self.funded()
+    }
+
+    pub fn total_sent(&self) -> U256 {
+        self.outcomes
+            .iter()
+            .filter(|o| o.is_funded())
+            .fold(U256::zero(), |sum, o| sum + o.transfer.amount)
+    }
+
+    pub fn total_gas(&self) -> U256 {
+        self.outcomes.iter().fold(U256::zero(), |sum, o| match o.status {
+            TransferStatus::Funded { gas_paid, .. } => sum + gas_paid,
+            TransferStatus::Pending { .. } | TransferStatus::Failed { .. } => sum,
+        })
+    }
+
+    pub fn print(&self) {
+        for o in &self.outcomes {
+            match &o.status {
+                TransferStatus::Funded { tx_hash, block, gas_paid } => println!(
+                    "✅ {:?} funded {} BNB | tx: {:?} | block {} | gas {} BNB",
+                    o.transfer.to,
+                    format_ether(o.transfer.amount),
+                    tx_hash,
+                    block,
+                    format_ether(*gas_paid)
+                ),
+                TransferStatus::Pending { tx_hash } => println!(
+                    "⏳ {:?} still pending ({} BNB) | tx: {:?}",
+                    o.transfer.to,
+                    format_ether(o.transfer.amount),
+                    tx_hash
+                ),
+                TransferStatus::Failed { tx_hash, error } => println!(
+                    "❌ {:?} not funded ({} BNB) | tx: {} | {}",
+                    o.transfer.to,
+                    format_ether(o.transfer.amount),
+                    tx_hash.map(|h| format!("{:?}", h)).unwrap_or_else(|| "-".to_string()),
+                    error
+                ),
+            }
+        }
+        println!(
+            "📦 Funded {} | failed {} | sent {} BNB | gas {} BNB",
+            self.funded(),
+            self.failed(),
+            format_ether(self.total_sent()),
+            format_ether(self.total_gas())
+        );
+    }
+
+    /// Errors if any wallet wasn't funded, so the run exits nonzero
+    pub fn ensure_all_funded(&self) -> Result<()> {
+        if self.failed() > 0 {
+            anyhow::bail!("{} of {} transfers not funded", self.failed(), self.outcomes.len());
+        }
+        Ok(())
+    }
+}
+
+/// This transfer's share of a status shared by `n` transfers in one tx (e.g. a disperse call).
+/// The first one carries the rounding remainder so the gas adds up exactly.
+pub fn shared_status(status: &TransferStatus, i: usize, n: usize) -> TransferStatus {
+    match status {
+        TransferStatus::Funded { tx_hash, block, gas_paid } => {
+            let n = U256::from(n.max(1));
+            TransferStatus::Funded {
+                tx_hash: *tx_hash,
+                block: *block,
+                gas_paid: *gas_paid / n + if i == 0 { *gas_paid % n } else { U256::zero() },
+            }
+        }
+        failed => failed.clone(),
+    }
+}
+
+/// Polls for the receipt until `timeout`, `None` if the tx never got mined
+pub async fn wait_for_receipt<M: Middleware>(
+    client: &M,
+    tx_hash: H256,
+    timeout: Duration,
+) -> Result<Option<TransactionReceipt>, M::Error> {
+    let start = Instant::now();
+    loop {
+        if let Some(receipt) = client.get_transaction_receipt(tx_hash).await? {
+            return Ok(Some(receipt));
+        }
+        if start.elapsed() >= timeout {
+            return Ok(None);
+        }
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    }
+}
+
+/// Turns a mined (or missing) receipt into the final status of a transfer
+pub fn receipt_status(tx_hash: H256, receipt: Option<TransactionReceipt>, timeout: Duration) -> TransferStatus {
+    match receipt {
+        Some(receipt) if receipt.status == Some(U64::one()) => TransferStatus::Funded {
+            tx_hash,
+            block: receipt.block_number.unwrap_or_default().as_u64(),
+            gas_paid: receipt.gas_used.unwrap_or_default() * receipt.effective_gas_price.unwrap_or_default(),
+        },
+        Some(_) => TransferStatus::Failed { tx_hash: Some(tx_hash), error: "reverted".to_string() },
+        None => {
+            println!("⚠️ {:?} not mined after {:?}", tx_hash, timeout);
+            TransferStatus::Pending { tx_hash }
+        }
+    }
+}
+
+/// Sends every transfer from the client's wallet without waiting in between, then waits
+/// for all receipts at once. Nonces are assigned locally; a send that reports an error keeps
+/// its nonce, since the tx may have gone out anyway, and is left pending if it never lands.
+pub async fn send_transfers<M: Middleware + 'static, S: Signer + 'static>(
+    client: &SignerMiddleware<M, S>,
+    transfers: &[Transfer],
+    mode: &SendMode,
+    receipt_timeout: Duration,
+) -> Result<TransferSummary> {
+    send_transfers_with(client, transfers, mode, receipt_timeout, |_, _| Ok(())).await
+}
+
+/// `send_transfers`, calling `on_signed(index, tx)` after each tx is signed and before it goes
+/// out (e.g. to journal it). An error there stops the run before that tx is broadcast.
+pub async fn send_transfers_with<M: Middleware + 'static, S: Signer + 'static>(
+    client: &SignerMiddleware<M, S>,
+    transfers: &[Transfer],
+    mode: &SendMode,
+    receipt_timeout: Duration,
+    mut on_signed: impl FnMut(usize, &SignedTx) -> Result<()>,
+) -> Result<TransferSummary> {
+    let sender = client.address();
+    let mut nonce = client
+        .get_transaction_count(sender, Some(BlockNumber::Pending.into()))
+        .await?;
+    let gas_price = client.get_gas_price().await?;
+
+    let mut sent = Vec::with_capacity(transfers.len());
+    for (i, transfer) in transfers.iter().enumerate() {
+        let tx = TransactionRequest::pay(transfer.to, transfer.amount)
+            .from(sender)
+            .nonce(nonce)
+            .gas(TRANSFER_GAS)
+            .gas_price(gas_price);
+        let signed = match sign_tx(client, tx).await {
+            Ok(signed) => signed,
+            Err(e) => {
+                println!("⚠️ Signing the transfer to {:?} failed: {}", transfer.to, e);
+                sent.push((*transfer, Err(e.to_string())));
+                continue;
+            }
+        };
+        on_signed(i, &signed)?;
+        match broadcast_tx(client, &signed, mode).await {
+            Ok(tx_hash) => {
+                println!("📤 Sent {} BNB to {:?} | nonce {} | tx: {:?}", format_ether(transfer.amount), transfer.to, nonce, tx_hash);
+                nonce += U256::one();
+                sent.push((*transfer, Ok(tx_hash)));
+            }
+            Err(e) => {
+                // The signed tx may still have reached a node or relay: its nonce stays used and it is
+                // waited for like the others, a resumed run rebroadcasts the same bytes if it got lost
+                println!("⚠️ Send to {:?} reported {}, keeping nonce {} | tx: {:?}", transfer.to, e, nonce, signed.tx_hash);
+                sent.push((*transfer, Ok(signed.tx_hash)));
+                let node_nonce = client
+                    .get_transaction_count(sender, Some(BlockNumber::Pending.into()))
+                    .await?;
+                // Never below ours: privately sent txs don't show in the node's pending count
+                nonce = (nonce + U256::one()).max(node_nonce);
+            }
+        }
+    }
+
+    println!("⏳ Waiting for {} receipts...", sent.iter().filter(|(_, r)| r.is_ok()).count());
+    let outcomes = join_all(sent.into_iter().map(|(transfer, sent)| async move {
+        let status = match sent {
+            Ok(tx_hash) => match wait_for_receipt(client, tx_hash, receipt_timeout).await {
+                Ok(receipt) => receipt_status(tx_hash, receipt, receipt_timeout),
+                Err(e) => {
+                    println!("⚠️ Could not fetch the receipt of {:?}: {}", tx_hash, e);
+                    TransferStatus::Pending { tx_hash }
+                }
+            },
+            Err(error) => TransferStatus::Failed { tx_hash: None, error },
+        };
+        TransferOutcome { transfer, status }
+    }))
+    .await;
+
+    Ok(TransferSummary { outcomes })
+}
@@ -2,8 +2,14 @@ pub mod utils;
 pub mod bundle;
 pub mod fourmeme;
 pub mod types;
+pub mod wallet;
+pub mod portfolio;
+pub mod funding;
 
 pub use utils::*;
 pub use types::*;
 pub use fourmeme::*;
-pub use bundle::*;
\ No newline at end of file
+pub use bundle::*;
+pub use wallet::*;
+pub use portfolio::*;
+pub use funding::*;
@@ -1,11 +1,14 @@
 use test_blox::utils::init_client;
+use test_blox::wallet::{create_mnemonic, has_mnemonic, import_mnemonic, migrate_plaintext_wallets, wallet_passphrase, export_keystore_dir, export_wallets_csv, import_keystore_dir, import_wallets_csv, CsvKeyColumn};
+use test_blox::utils::{plan_distribution, read_all_wallets, sweep, sweep_tokens};
+use test_blox::portfolio::{portfolio_report, ReportFormat};
+use test_blox::funding::{run_distribution, FundingAmount, FundingMode, FundingPlan, RunJournal, RunKind, SweepTxType, DEFAULT_RECEIPT_TIMEOUT};
+use std::path::Path;
 use ethers::providers::{Provider, Http};
 use ethers::providers::Middleware;
-use test_blox::types::WalletInfo;
 use ethers::prelude::*;
-use test_blox::utils::{gen_several_wallets, distribute_bnb};
-use test_blox::bundle::{bundle_bsc_tx};
-use test_blox::fourmeme::{get_create_new_token_tx, approve_token, get_buy_token_tx};
+use test_blox::bundle::{relays_from_env, BundleSimulation, fan_out_bundle_window, submit_on_new_heads, print_relay_summary, BundleBuilder, BuilderTip, SendMode, SimulationBackend};
+use test_blox::fourmeme::{get_create_new_token_tx, approve_token, get_buy_token_tx, FourMemeApi};
 use std::env;
 use std::{io, sync::Arc};
 
@@ -18,21 +21,31 @@ async fn main() -> anyhow::Result<()> {
     dotenv::dotenv().ok(); // load .env variables
 
     // Get the number of wallets to generate
-    let wallet_num: usize = env::var("WALLET_NUM")
+    let _wallet_num: usize = env::var("WALLET_NUM")
         .unwrap_or_else(|_| "5".to_string()) // default 5 wallets
         .parse()
         .expect("WALLET_NUM must be a number");
     println!("Block number: {}", bn);
-    // Load wallet number and min/max BNB from .env
-    let min_bnb: f64 = env::var("MIN_BNB")?.parse()?;
-    let max_bnb: f64 = env::var("MAX_BNB")?.parse()?;
+    // Random MIN_BNB..MAX_BNB per wallet or top up to a target (FUNDING_AMOUNT)
+    let funding_amount = FundingAmount::from_env()?;
     let token_manager_address: Address = env::var("TOKEN_MANAGER2")?.parse()?;
     let token_address: Address = env::var("TOKEN_ADDRESS")?.parse()?;
+    // Public mempool or private relay for single txs (TX_SEND_MODE)
+    let send_mode = SendMode::from_env()?;
+    // One transfer per wallet or a single Disperse call (FUNDING_MODE)
+    let funding_mode = FundingMode::from_env()?;
+    // four.meme API (FOUR_MEME_API_URL), keeps the login session between bundles
+    let four_meme = FourMemeApi::from_env();
     loop {
         println!("1) Distribute BNB to wallets");       // description: funding wallets
         println!("2) Bundle create and buy tokens");              // description: purchase tokens in batch
         println!("3) Sell tokens and sweep back");     // description: sell & consolidate
         println!("4) Exit"); 
+        println!("5) Encrypt plaintext wallet files");  // description: move src/wallets keys into keystores
+        println!("6) Set up HD wallet mnemonic");       // description: one backed-up secret for all sub-wallets
+        println!("7) Import wallets");                  // description: CSV file or keystore directory
+        println!("8) Export wallets");                  // description: CSV with keystore paths or keystore directory
+        println!("9) Portfolio report");                // description: balances, nonces and pending txs per wallet
         print!("> ");
         use std::io::Write;
         io::stdout().flush().unwrap();
@@ -44,43 +57,201 @@ async fn main() -> anyhow::Result<()> {
         match choice {
             "1" => {
                 println!("🚀 Distributing BNB...");
-                let amount_per_wallet = 0.01; // Example
+                let resume = prompt_line("📒 Run id to resume (empty to start a new run): ")?;
+                let mut journal = if resume.is_empty() {
+                    let saved = prompt_line("📂 Saved plan to run (empty to plan a new distribution): ")?;
+                    let plan = if saved.is_empty() {
+                        let wallets = read_all_wallets()?.into_iter().filter(|w| !w.retired).collect();
+                        let plan = plan_distribution(client.clone(), wallets, &funding_amount, &funding_mode).await?;
+                        plan.print();
+                        let save_to = prompt_line("💾 Save plan to (empty to run it now): ")?;
+                        if !save_to.is_empty() {
+                            plan.save(Path::new(&save_to))?;
+                            continue;
+                        }
+                        plan
+                    } else {
+                        let mut plan = FundingPlan::load(Path::new(&saved))?;
+                        // Refuses a plan made for another chain or treasury before anything is journaled
+                        plan.refresh(&client).await?;
+                        plan.print();
+                        plan
+                    };
+                    RunJournal::from_plan(&RunJournal::new_run_id(RunKind::Distribute), &plan)?
+                } else {
+                    RunJournal::load(&resume)?.ok_or_else(|| anyhow::anyhow!("No journal for run {}", resume))?
+                };
+                println!("📒 Run id {} (enter it again to resume)", journal.run_id);
+                // Refuses to send anything if the treasury can't cover what's left of the run
+                let summary = run_distribution(&client, &mut journal, &send_mode, DEFAULT_RECEIPT_TIMEOUT).await?;
+                journal.print();
+                // Nonzero exit so scripts notice a half-funded batch
+                summary.ensure_all_funded()?;
             }
             "2" => {
                 println!("🟢 Bundle buying tokens...");
-                let private_key = env::var("PRIVATE_KEY")?;
+                // Same signer as the treasury client (TREASURY_SIGNER), on the bundle provider's chain
+                let chain_id = provider.get_chainid().await?.as_u64();
+                let wallet = client.signer().clone().with_chain_id(chain_id);
 
-                // Parse into a LocalWallet and set chain id (BSC mainnet = 56)
-                let wallet: LocalWallet = private_key.parse::<LocalWallet>()?.with_chain_id(56u64);
-
-                let bloxroute_auth = std::env::var("BLOXROUTE_AUTH_HEADER")?;
-                let token_create_tx = get_create_new_token_tx(wallet.clone(), provider.clone(), "assets/image.png", "assets/config.json").await?;
-                let token_create_signed_tx = wallet.sign_transaction(&token_create_tx).await?.to_string();
+                // Relays listed in BUNDLE_RELAYS, or the single BUNDLE_RELAY (bloxroute, 48club, blockrazor, bep322)
+                let relays = relays_from_env()?;
+                let token_create_tx = get_create_new_token_tx(&four_meme, wallet.clone(), provider.clone(), "assets/image.png", "assets/config.json").await?;
 
                 // 1️⃣ Approve token first (if needed)
-                approve_token(wallet.clone(), provider.clone(), token_address, token_manager_address).await?;
+                approve_token(wallet.clone(), provider.clone(), token_address, token_manager_address, &send_mode).await?;
                 println!("✅ Approved TokenManager2 to spend your tokens");
 
                 // 2️⃣ Build a buyToken transaction (buy exact token amount)
                 let token_amount = 10.0; // 10 tokens
                 let max_bnb = "0.2";     // max 0.2 BNB
                 let buy_tx = get_buy_token_tx(wallet.clone(), provider.clone(), token_manager_address, token_address, token_amount, max_bnb).await?;
-                let token_buy_signed_tx = wallet.sign_transaction(&buy_tx).await?.to_string();
-                let signed_txs = vec![
-                    token_create_signed_tx,
-                    token_buy_signed_tx
-                ];
-                bundle_bsc_tx(signed_txs, provider.clone(), &bloxroute_auth).await?;
+
+                // Nonces and gas are filled at signing time, and again if the bundle goes stale
+                let mut builder = BundleBuilder::new(provider.clone());
+                builder.push(token_create_tx, wallet.clone());
+                builder.push(buy_tx, wallet.clone());
+                if let Some(tip) = BuilderTip::from_env(wallet.clone())? {
+                    builder.tip(tip);
+                }
+
+                // 3️⃣ Simulate the exact signed bundle before paying a relay
+                let signed = builder.build().await?;
+                let rpc_url = env::var("RPC_URL").unwrap_or_else(|_| "https://bsc-dataseed.binance.org".to_string());
+                // Every rebuild of a stale bundle is simulated again before it goes out
+                let simulation = BundleSimulation {
+                    backend: SimulationBackend::for_relay(relays[0].as_ref(), &rpc_url),
+                    tokens: vec![token_address],
+                };
+                simulation.check(provider.clone(), &signed).await?;
+
+                // 4️⃣ Target the next BUNDLE_BLOCK_WINDOW blocks on every relay until the bundle lands
+                let window: u64 = env::var("BUNDLE_BLOCK_WINDOW")
+                    .unwrap_or_else(|_| "3".to_string())
+                    .parse()?;
+                // With BUNDLE_WS_URL set, fire BUNDLE_SEND_OFFSET_MS after each new head instead of polling
+                let (status, outcomes) = match env::var("BUNDLE_WS_URL") {
+                    Ok(ws_url) => {
+                        let offset_ms: u64 = env::var("BUNDLE_SEND_OFFSET_MS")
+                            .unwrap_or_else(|_| "0".to_string())
+                            .parse()?;
+                        let offset = std::time::Duration::from_millis(offset_ms);
+                        let (status, _timings, outcomes) =
+                            submit_on_new_heads(&ws_url, &relays, &builder, signed, provider.clone(), offset, window, Some(&simulation)).await?;
+                        (status, outcomes)
+                    }
+                    Err(_) => fan_out_bundle_window(&relays, &builder, signed, provider.clone(), window, Some(&simulation)).await?,
+                };
+                print_relay_summary(&outcomes);
+                println!("{}", status);
             }
             "3" => {
                 println!("🔵 Selling tokens and sweeping...");
+                let wallets: Vec<_> = read_all_wallets()?.into_iter().filter(|w| !w.retired).collect();
+                let confirm = prompt_line(&format!(
+                    "⚠️ Sweep token {:?} then BNB from {} wallets back to {:?}? (y/N): ",
+                    token_address,
+                    wallets.len(),
+                    client.address()
+                ))?;
+                if !confirm.eq_ignore_ascii_case("y") {
+                    println!("↩️ Sweep cancelled");
+                    continue;
+                }
+                let passphrase = wallet_passphrase()?;
+
+                // Tokens first, while the wallets still hold BNB for gas
+                let resume = prompt_line("📒 Token sweep run id to resume (empty to start a new run): ")?;
+                let run_id = if resume.is_empty() { RunJournal::new_run_id(RunKind::TokenSweep) } else { resume };
+                println!("📒 Token sweep run id {} (enter it again to resume)", run_id);
+                let tokens = sweep_tokens(client.clone(), wallets.clone(), token_address, client.address(), &passphrase, &send_mode, &run_id).await?;
+                tokens.ensure_all_funded()?;
+
+                // SWEEP_TX_TYPE = legacy (default) | eip1559
+                let resume = prompt_line("📒 BNB sweep run id to resume (empty to start a new run): ")?;
+                let run_id = if resume.is_empty() { RunJournal::new_run_id(RunKind::Sweep) } else { resume };
+                println!("📒 BNB sweep run id {} (enter it again to resume)", run_id);
+                let summary = sweep(client.clone(), wallets, &passphrase, &send_mode, SweepTxType::from_env()?, &run_id).await?;
+                summary.ensure_all_funded()?;
             }
             "4" => {
                 println!("👋 Exiting...");
                 break;
             }
-            _ => println!("❌ Invalid option, choose 1-4."),
+            "5" => {
+                let passphrase = wallet_passphrase()?;
+                let migrated = migrate_plaintext_wallets(&passphrase)?;
+                println!("🔐 {} plaintext keys moved into keystores", migrated);
+            }
+            "6" => {
+                if has_mnemonic() {
+                    println!("⚠️ A mnemonic is already set up, sub-wallets derive from it.");
+                    continue;
+                }
+                let passphrase = wallet_passphrase()?;
+                let phrase = rpassword::prompt_password("🌱 Existing mnemonic (empty to generate a new one): ")?;
+                if phrase.trim().is_empty() {
+                    let phrase = create_mnemonic(&passphrase)?;
+                    println!("📝 Write this mnemonic down, it is the only backup of every sub-wallet:\n{}", *phrase);
+                } else {
+                    import_mnemonic(&phrase, &passphrase)?;
+                    println!("✅ Mnemonic imported");
+                }
+            }
+            "7" => {
+                let path = prompt_line("📂 CSV file or keystore directory: ")?;
+                let group = prompt_line("🏷️ Group (empty for none): ")?;
+                let group = (!group.is_empty()).then_some(group.as_str());
+                let passphrase = wallet_passphrase()?;
+                let source_passphrase = rpassword::prompt_password("🔑 Passphrase of the imported keystores (empty if same): ")?;
+                let source_passphrase = if source_passphrase.is_empty() { passphrase.as_str() } else { source_passphrase.as_str() };
+                let path = Path::new(&path);
+                let report = if path.is_dir() {
+                    import_keystore_dir(path, group, source_passphrase, &passphrase)?
+                } else {
+                    import_wallets_csv(path, group, source_passphrase, &passphrase)?
+                };
+                report.print_summary();
+            }
+            "8" => {
+                let path = prompt_line("📂 Export to (.csv file or directory): ")?;
+                let passphrase = wallet_passphrase()?;
+                let wallets = read_all_wallets()?;
+                let path = Path::new(&path);
+                if path.extension().is_some_and(|ext| ext == "csv") {
+                    export_wallets_csv(path, &wallets, &passphrase, CsvKeyColumn::KeystorePath)?;
+                } else {
+                    export_keystore_dir(path, &wallets, &passphrase)?;
+                }
+            }
+            "9" => {
+                // PORTFOLIO_TOKENS (comma separated) defaults to TOKEN_ADDRESS, PORTFOLIO_FORMAT = table | json
+                let tokens = match env::var("PORTFOLIO_TOKENS") {
+                    Ok(list) => list.split(',').map(|t| t.trim().parse()).collect::<Result<Vec<Address>, _>>()?,
+                    Err(_) => vec![token_address],
+                };
+                let format: ReportFormat = env::var("PORTFOLIO_FORMAT").unwrap_or_else(|_| "table".to_string()).parse()?;
+                let multicall_address = env::var("MULTICALL3_ADDRESS").ok().map(|a| a.parse()).transpose()?;
+
+                let mut wallets = vec![("main".to_string(), client.address())];
+                for w in read_all_wallets()?.into_iter().filter(|w| !w.retired) {
+                    let label = w.label.or(w.group).unwrap_or_else(|| "-".to_string());
+                    wallets.push((label, w.address));
+                }
+                let report = portfolio_report(provider.clone(), multicall_address, &wallets, &tokens).await?;
+                report.print(format)?;
+            }
+            _ => println!("❌ Invalid option, choose 1-9."),
         }
     }
     Ok(())
 }
+
+fn prompt_line(prompt: &str) -> io::Result<String> {
+    use std::io::Write;
+    print!("{}", prompt);
+    io::stdout().flush()?;
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    Ok(input.trim().to_string())
+}
@@ -0,0 +1,272 @@
+use anyhow::{Context, Result};
+use ethers::abi::Token;
+use ethers::prelude::*;
+use ethers::utils::{format_ether, format_units};
+use serde_json::{Value, json};
+use std::{str::FromStr, sync::Arc};
+
+use crate::fourmeme::buy::ERC20;
+
+/// Calls per `aggregate3`, keeps each eth_call well under node gas caps
+pub const MULTICALL_CHUNK: usize = 300;
+/// Requests per JSON-RPC batch for nonces
+pub const NONCE_BATCH_CHUNK: usize = 100;
+
+/// Table for people, JSON for scripts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReportFormat {
+    #[default]
+    Table,
+    Json,
+}
+
+impl FromStr for ReportFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "table" => Ok(ReportFormat::Table),
+            "json" => Ok(ReportFormat::Json),
+            other => anyhow::bail!("Unknown report format: {}", other),
+        }
+    }
+}
+
+/// One wallet's row in the report
+#[derive(Debug, Clone)]
+pub struct WalletBalance {
+    pub label: String,
+    pub address: Address,
+    pub bnb: U256,
+    pub tokens: Vec<Option<U256>>, // per report token, `None` if balanceOf failed
+    pub nonce: U256,
+    pub pending_nonce: U256,
+}
+
+impl WalletBalance {
+    /// Txs sitting in the mempool
+    pub fn pending_txs(&self) -> U256 {
+        self.pending_nonce.saturating_sub(self.nonce)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Portfolio {
+    pub block: u64,
+    pub tokens: Vec<Address>,
+    pub wallets: Vec<WalletBalance>,
+}
+
+impl Portfolio {
+    pub fn total_bnb(&self) -> U256 {
+        self.wallets.iter().fold(U256::zero(), |sum, w| sum + w.bnb)
+    }
+
+    pub fn total_tokens(&self) -> Vec<U256> {
+        (0..self.tokens.len())
+            .map(|i| {
+                self.wallets
+                    .iter()
+                    .fold(U256::zero(), |sum, w| sum + w.tokens[i].unwrap_or_default())
+            })
+            .collect()
+    }
+
+    pub fn print(&self, format: ReportFormat) -> Result<()> {
+        match format {
+            ReportFormat::Table => self.print_table(),
+            ReportFormat::Json => println!("{}", serde_json::to_string_pretty(&self.to_json())?),
+        }
+        Ok(())
+    }
+
+    pub fn print_table(&self) {
+        println!("📊 Portfolio at block {}", self.block);
+        let mut header = format!("{:<14} {:<42} {:>14}", "label", "address", "BNB");
+        for token in &self.tokens {
+            header += &format!(" {:>18}", short_address(*token));
+        }
+        header += &format!(" {:>6} {:>8}", "nonce", "pending");
+        println!("{}", header);
+
+        for w in &self.wallets {
+            let mut line = format!("{:<14} {:<42?} {:>14}", w.label, w.address, format_bnb(w.bnb));
+            for balance in &w.tokens {
+                line += &format!(" {:>18}", balance.map(format_token).unwrap_or_else(|| "-".to_string()));
+            }
+            line += &format!(" {:>6} {:>8}", w.nonce, w.pending_txs());
+            println!("{}", line);
+        }
+
+        let mut total = format!("{:<14} {:<42} {:>14}", "total", "", format_bnb(self.total_bnb()));
+        for balance in self.total_tokens() {
+            total += &format!(" {:>18}", format_token(balance));
+        }
+        println!("{}", total);
+    }
+
+    /// Amounts as full-precision decimal strings in BNB / whole tokens
+    pub fn to_json(&self) -> Value {
+        let wallets: Vec<Value> = self
+            .wallets
+            .iter()
+            .map(|w| {
+                json!({
+                    "label": w.label,
+                    "address": w.address,
+                    "bnb": format_ether(w.bnb),
+                    "tokens": self.tokens.iter().zip(&w.tokens).map(|(token, balance)| {
+                        json!({ "token": token, "balance": balance.map(token_units) })
+                    }).collect::<Vec<_>>(),
+                    "nonce": w.nonce.as_u64(),
+                    "pending_txs": w.pending_txs().as_u64(),
+                })
+            })
+            .collect();
+        json!({
+            "block": self.block,
+            "total_bnb": format_ether(self.total_bnb()),
+            "total_tokens": self.tokens.iter().zip(self.total_tokens()).map(|(token, total)| {
+                json!({ "token": token, "balance": token_units(total) })
+            }).collect::<Vec<_>>(),
+            "wallets": wallets,
+        })
+    }
+}
+
+fn short_address(address: Address) -> String {
+    let hex = format!("{:?}", address);
+    format!("{}…{}", &hex[..6], &hex[hex.len() - 4..])
+}
+
+fn format_bnb(wei: U256) -> String {
+    let bnb: f64 = format_ether(wei).parse().unwrap_or_default();
+    format!("{:.6}", bnb)
+}
+
+/// Full precision, 18 decimals like every four.meme token
+fn token_units(amount: U256) -> String {
+    format_units(amount, 18u32).unwrap_or_default()
+}
+
+fn format_token(amount: U256) -> String {
+    let tokens: f64 = token_units(amount).parse().unwrap_or_default();
+    format!("{:.2}", tokens)
+}
+
+/// BNB and token balances for every wallet through Multicall3, pinned to one block.
+/// `multicall_address` is only needed on chains ethers doesn't know a Multicall3 for.
+async fn multicall_balances(
+    provider: Arc<Provider<Http>>,
+    multicall_address: Option<Address>,
+    addresses: &[Address],
+    tokens: &[Address],
+    block: u64,
+) -> Result<Vec<(U256, Vec<Option<U256>>)>> {
+    let mut multicall = Multicall::new(provider.clone(), multicall_address)
+        .await
+        .context("Multicall3 not available, set MULTICALL3_ADDRESS")?
+        .block(block);
+    let contracts: Vec<_> = tokens.iter().map(|t| ERC20::new(*t, provider.clone())).collect();
+    let per_wallet = 1 + tokens.len();
+    let wallets_per_chunk = (MULTICALL_CHUNK / per_wallet).max(1);
+
+    let mut balances = Vec::with_capacity(addresses.len());
+    for chunk in addresses.chunks(wallets_per_chunk) {
+        multicall.clear_calls();
+        for address in chunk {
+            multicall.add_get_eth_balance(*address, false);
+            for contract in &contracts {
+                multicall.add_call(contract.balance_of(*address), true);
+            }
+        }
+
+        let results = multicall.call_raw().await?;
+        for row in results.chunks(per_wallet) {
+            let bnb = match &row[0] {
+                Ok(Token::Uint(bnb)) => *bnb,
+                other => anyhow::bail!("Unexpected getEthBalance result: {:?}", other),
+            };
+            let token_balances = row[1..]
+                .iter()
+                .map(|r| match r {
+                    Ok(Token::Uint(balance)) => Some(*balance),
+                    _ => None,
+                })
+                .collect();
+            balances.push((bnb, token_balances));
+        }
+    }
+    Ok(balances)
+}
+
+/// `eth_getTransactionCount` at latest and pending for every address, as JSON-RPC batches.
+/// Falls back to one call per address if the node doesn't take batches.
+async fn batch_nonces(provider: &Provider<Http>, addresses: &[Address]) -> Result<Vec<(U256, U256)>> {
+    let client = reqwest::Client::new();
+    let url = provider.as_ref().url().clone();
+    let mut nonces = Vec::with_capacity(addresses.len());
+
+    for chunk in addresses.chunks(NONCE_BATCH_CHUNK) {
+        let batch: Vec<Value> = chunk
+            .iter()
+            .flat_map(|address| [(address, "latest"), (address, "pending")])
+            .enumerate()
+            .map(|(id, (address, tag))| {
+                json!({ "jsonrpc": "2.0", "id": id, "method": "eth_getTransactionCount", "params": [address, tag] })
+            })
+            .collect();
+        // Nodes without batch support answer with an error object or a non-JSON body
+        let response: Value = client.post(url.clone()).json(&batch).send().await?.json().await.unwrap_or_default();
+
+        let Some(responses) = response.as_array().filter(|r| r.len() == batch.len()) else {
+            println!("⚠️ Node rejected the JSON-RPC batch, reading nonces one by one");
+            for address in chunk {
+                let nonce = provider.get_transaction_count(*address, Some(BlockNumber::Latest.into())).await?;
+                let pending = provider.get_transaction_count(*address, Some(BlockNumber::Pending.into())).await?;
+                nonces.push((nonce, pending));
+            }
+            continue;
+        };
+
+        // Batch answers may come back in any order
+        let mut counts = vec![U256::zero(); batch.len()];
+        for r in responses {
+            let id = r["id"].as_u64().context("Batch response without id")? as usize;
+            let count = r["result"].as_str().with_context(|| format!("Batch request failed: {}", r))?;
+            *counts.get_mut(id).context("Batch response with unknown id")? = U256::from_str_radix(count.trim_start_matches("0x"), 16)?;
+        }
+        nonces.extend(counts.chunks(2).map(|pair| (pair[0], pair[1])));
+    }
+    Ok(nonces)
+}
+
+/// Builds the report for `wallets` (label, address), e.g. the main wallet plus `read_all_wallets`
+pub async fn portfolio_report(
+    provider: Arc<Provider<Http>>,
+    multicall_address: Option<Address>,
+    wallets: &[(String, Address)],
+    tokens: &[Address],
+) -> Result<Portfolio> {
+    let block = provider.get_block_number().await?.as_u64();
+    let addresses: Vec<Address> = wallets.iter().map(|(_, address)| *address).collect();
+
+    let balances = multicall_balances(provider.clone(), multicall_address, &addresses, tokens, block).await?;
+    let nonces = batch_nonces(&provider, &addresses).await?;
+
+    let wallets = wallets
+        .iter()
+        .zip(balances)
+        .zip(nonces)
+        .map(|(((label, address), (bnb, tokens)), (nonce, pending_nonce))| WalletBalance {
+            label: label.clone(),
+            address: *address,
+            bnb,
+            tokens,
+            nonce,
+            pending_nonce,
+        })
+        .collect();
+
+    Ok(Portfolio { block, tokens: tokens.to_vec(), wallets })
+}
@@ -1,8 +1,60 @@
 use ethers::types::Address;
 use serde::{Serialize, Deserialize};
+use std::{path::PathBuf, time::{SystemTime, UNIX_EPOCH}};
 
+/// Where a wallet's key comes from
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum KeySource {
+    Keystore { keystore: PathBuf }, // Web3 Secret Storage file, unlocked with the wallet passphrase
+    Derived { hd_index: u32 },      // m/44'/60'/0'/0/<hd_index> of the stored mnemonic
+}
+
+/// How the wallet got into the registry
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum WalletOrigin {
+    #[default]
+    Generated,
+    Imported,
+    Derived,
+}
+
+/// One registry entry. Fields past `key` default so older index files still load.
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct WalletInfo {
     pub address: Address,
-    pub private_key: String,
+    #[serde(flatten)]
+    pub key: KeySource,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    #[serde(default)]
+    pub created_at: u64, // unix seconds, 0 if unknown
+    #[serde(default)]
+    pub origin: WalletOrigin,
+    #[serde(default)]
+    pub retired: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+}
+
+impl WalletInfo {
+    pub fn new(address: Address, key: KeySource, origin: WalletOrigin) -> Self {
+        let created_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        Self {
+            address,
+            key,
+            label: None,
+            group: None,
+            created_at,
+            origin,
+            retired: false,
+            notes: None,
+        }
+    }
 }
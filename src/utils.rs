@@ -1,21 +1,23 @@
 // src/client.rs (or wherever you like)
 use anyhow::{Context, Result};
 use dotenvy::dotenv;
-use std::time::{SystemTime, UNIX_EPOCH};
 use ethers::prelude::*;
-use std::{fs, path::Path};
-use rand::Rng;
-use std::{env, sync::Arc, time::Duration};
-use std::str::FromStr;
-use serde_json::from_str;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::utils::format_ether;
+use std::collections::HashSet;
+use std::path::Path;
+use std::{env, ops::Range, sync::Arc, time::Duration};
 use crate::types::WalletInfo;
+use crate::wallet::{HD_PATH_PREFIX, WALLETS_DIR, WalletRegistry, WalletSigner, derive_wallet_range, load_mnemonic, next_hd_index, unlock_wallets};
+use crate::bundle::{SendMode, broadcast_tx, sign_tx};
+use crate::fourmeme::buy::ERC20;
+use crate::funding::{DEFAULT_RECEIPT_TIMEOUT, FundingAmount, FundingMode, FundingPlan, JournalEntry, JournalStatus, RunJournal, RunKind, SweepGas, SweepTxType, TransferSummary, plan_transfers, run_distribution};
 
-pub fn init_client() -> Result<Arc<SignerMiddleware<Provider<Http>, LocalWallet>>> {
+/// Treasury client; the signer comes from `TREASURY_SIGNER` (local key, keystore or remote)
+pub fn init_client() -> Result<Arc<SignerMiddleware<Provider<Http>, WalletSigner>>> {
     dotenv().ok();
     let rpc_url = env::var("RPC_URL")
         .unwrap_or_else(|_| "https://bsc-dataseed.binance.org".to_string());
-    let private_key = env::var("PRIVATE_KEY")
-        .context("Missing PRIVATE_KEY in environment (.env)")?;
     let chain_id: u64 = env::var("CHAIN_ID")
         .ok()
         .and_then(|s| s.parse::<u64>().ok())
@@ -23,144 +25,300 @@ pub fn init_client() -> Result<Arc<SignerMiddleware<Provider<Http>, LocalWallet>
     let provider = Provider::<Http>::try_from(rpc_url.as_str())
         .context("Failed to create HTTP provider from RPC_URL")?
         .interval(Duration::from_millis(300));
-    let wallet: LocalWallet = private_key
-        .parse::<LocalWallet>()
-        .context("Failed to parse PRIVATE_KEY as LocalWallet")?
-        .with_chain_id(chain_id);
+    let wallet = WalletSigner::from_env()?.with_chain_id(chain_id);
     let client = SignerMiddleware::new(provider, wallet);
     Ok(Arc::new(client))
 }
 
 
-pub fn gen_several_wallets(wallet_num: usize) -> Result<Vec<WalletInfo>> {
-    let mut wallet_list = Vec::new();
-
-    for _ in 0..wallet_num {
-        let wallet = LocalWallet::new(&mut rand::thread_rng());
-        wallet_list.push(WalletInfo {
-            address: wallet.address(),
-            private_key: hex::encode(wallet.signer().to_bytes()),
-        });
+/// Derives the next `wallet_num` HD wallets from the stored mnemonic into a new registry batch tagged `group`
+pub fn gen_several_wallets(wallet_num: usize, group: Option<&str>, passphrase: &str) -> Result<Vec<WalletInfo>> {
+    let mnemonic = load_mnemonic(passphrase)?;
+    let mut registry = WalletRegistry::load()?;
+    let wallets: Vec<WalletInfo> = registry.wallets().cloned().collect();
+    let start = next_hd_index(&wallets);
+    let mut wallet_list = derive_wallet_range(&mnemonic, start..start + wallet_num as u32)?;
+    for w in &mut wallet_list {
+        w.group = group.map(str::to_string);
     }
 
     // ✅ Save to timestamped JSON file
-    let timestamp = SystemTime::now()
-        .duration_since(UNIX_EPOCH)?
-        .as_secs();
-    let folder_path = format!("src/wallets");
-    fs::create_dir_all(&folder_path)?;
-    let file_path = format!("{}/{}.json", folder_path, timestamp);
-
-    let json_data = serde_json::to_string_pretty(&wallet_list)?;
-    fs::write(&file_path, json_data)?;
-
-    println!("✅ Wallets saved to: {}", file_path);
+    let file_path = registry.add_batch(wallet_list.clone())?;
+    println!("✅ Wallets saved to: {}", file_path.display());
 
     Ok(wallet_list)
 }
 
-pub async fn distribute_bnb(
-    main_client: Arc<SignerMiddleware<Provider<Http>, LocalWallet>>,
+/// Plans one transfer per wallet sized by `amount`, priced against the treasury
+pub async fn plan_distribution(
+    main_client: Arc<SignerMiddleware<Provider<Http>, WalletSigner>>,
     wallet_list: Vec<WalletInfo>,
-    min_amount: f64,
-    max_amount: f64,
-) -> Result<()> {
-    let provider = main_client.provider();
-    let balance = provider.get_balance(main_client.address(), None).await?;
-    println!("💰 Main wallet balance: {} wei", balance);
-
-    let mut rng = rand::thread_rng();
+    amount: &FundingAmount,
+    funding: &FundingMode,
+) -> Result<FundingPlan> {
+    let addresses: Vec<Address> = wallet_list.iter().map(|w| w.address).collect();
+    let transfers = plan_transfers(&main_client, &addresses, amount).await?;
+    let mut plan = FundingPlan::new(&main_client, transfers, *funding).await?;
+    if let FundingAmount::TopUp { target } = amount {
+        plan.top_up_target = Some(*target);
+    }
+    Ok(plan)
+}
 
-    for w in wallet_list {
-        // Random amount between min and max
-        let amount_bnb = rng.gen_range(min_amount..=max_amount);
-        let amount_wei = ethers::utils::parse_ether(amount_bnb)?;
+/// Funds every wallet as run `run_id`: a new run is planned and must be covered by the treasury,
+/// an existing one resumes from its journal and only sends what's missing
+pub async fn distribute_bnb(
+    main_client: Arc<SignerMiddleware<Provider<Http>, WalletSigner>>,
+    wallet_list: Vec<WalletInfo>,
+    amount: &FundingAmount,
+    funding: &FundingMode,
+    mode: &SendMode,
+    run_id: &str,
+) -> Result<TransferSummary> {
+    let mut journal = match RunJournal::load(run_id)? {
+        Some(journal) => journal,
+        None => {
+            let plan = plan_distribution(main_client.clone(), wallet_list, amount, funding).await?;
+            plan.print();
+            plan.ensure_covered()?;
+            RunJournal::from_plan(run_id, &plan)?
+        }
+    };
 
-        let tx = TransactionRequest::new()
-            .to(w.address)
-            .value(amount_wei);
+    let summary = run_distribution(&main_client, &mut journal, mode, DEFAULT_RECEIPT_TIMEOUT).await?;
+    journal.print();
+    Ok(summary)
+}
 
-        let pending = main_client.send_transaction(tx, None).await?;
-        println!(
-            "✅ Sent {:.6} BNB to {:?} | tx: {:?}",
-            amount_bnb, w.address, pending.tx_hash()
-        );
+/// Reads all JSON wallet files from `src/wallets` folder
+pub fn read_all_wallets() -> Result<Vec<WalletInfo>> {
+    if !Path::new(WALLETS_DIR).exists() {
+        println!("⚠️ Folder {} does not exist.", WALLETS_DIR);
     }
+    let all_wallets = WalletRegistry::load()?.into_wallets();
+    println!("✅ Loaded {} wallets from {}", all_wallets.len(), WALLETS_DIR);
+    Ok(all_wallets)
+}
 
-    Ok(())
+/// Re-derives HD wallets `range` straight from the stored mnemonic, no index files needed
+pub fn read_derived_wallets(range: Range<u32>, passphrase: &str) -> Result<Vec<WalletInfo>> {
+    let mnemonic = load_mnemonic(passphrase)?;
+    let wallets = derive_wallet_range(&mnemonic, range.clone())?;
+    println!("✅ Derived {} wallets at {}{:?}", wallets.len(), HD_PATH_PREFIX, range);
+    Ok(wallets)
 }
 
-/// Reads all JSON wallet files from `src/wallets` folder
-pub fn read_all_wallets() -> Result<Vec<WalletInfo>> {
-    let mut all_wallets = Vec::new();
-    let folder_path = "src/wallets";
+/// What one wallet of a sweep run sends, decided from its balance at send time
+enum SweepStep {
+    Send { tx: Box<TypedTransaction>, amount: U256 },
+    /// Nothing can go out now (e.g. balance below the fee), a rerun tries again
+    Fail(String),
+    /// Nothing left to move, the entry leaves the run
+    Drop,
+}
 
-    // Check if the folder exists
-    if !Path::new(folder_path).exists() {
-        println!("⚠️ Folder {} does not exist.", folder_path);
-        return Ok(all_wallets);
-    }
+type SweepClient = SignerMiddleware<Provider<Http>, LocalWallet>;
 
-    // Iterate over all files in the folder
-    for entry in fs::read_dir(folder_path)? {
-        let entry = entry?;
-        let path = entry.path();
-
-        if path.is_file() {
-            if let Some(ext) = path.extension() {
-                if ext == "json" {
-                    let content = fs::read_to_string(&path)?;
-                    let wallets: Vec<WalletInfo> = from_str(&content)?;
-                    all_wallets.extend(wallets);
-                }
+/// Sends whatever a sweep journal is still missing: settles what the last attempt left pending,
+/// unlocks every sending wallet, lets `step` build each wallet's tx from its current balance,
+/// then waits for all receipts at once. Shared by the BNB and token sweeps.
+async fn run_sweep_journal(
+    provider: &Provider<Http>,
+    journal: &mut RunJournal,
+    wallet_list: &[WalletInfo],
+    passphrase: &str,
+    mode: &SendMode,
+    mut step: impl AsyncFnMut(Arc<SweepClient>) -> Result<SweepStep>,
+) -> Result<()> {
+    let chain_id = provider.get_chainid().await?.as_u64();
+    journal.settle_pending(provider, mode, DEFAULT_RECEIPT_TIMEOUT).await?;
+
+    let missing = journal.missing();
+    let missing_wallets: Vec<WalletInfo> = missing
+        .iter()
+        .map(|i| {
+            let from = journal.entries[*i].from;
+            wallet_list
+                .iter()
+                .find(|w| w.address == from)
+                .cloned()
+                .with_context(|| format!("Wallet {:?} of run {} is not in the registry", from, journal.run_id))
+        })
+        .collect::<Result<_>>()?;
+
+    // Unlock every key up front so a wrong passphrase fails before anything is sent
+    let wallets = unlock_wallets(&missing_wallets, passphrase)?;
+    let mut emptied = Vec::new();
+    for (i, wallet) in missing.into_iter().zip(wallets) {
+        let client = Arc::new(SignerMiddleware::new(provider.clone(), wallet.with_chain_id(chain_id)));
+        let to = journal.entries[i].to;
+
+        let (tx, amount) = match step(client.clone()).await? {
+            SweepStep::Send { tx, amount } => (*tx, amount),
+            SweepStep::Fail(error) => {
+                println!("⚠️ Wallet {:?}: {}", client.address(), error);
+                journal.set_status(i, JournalStatus::Failed { tx_hash: None, error })?;
+                continue;
+            }
+            SweepStep::Drop => {
+                println!("⚠️ Wallet {:?} has nothing left to sweep, dropping it from the run.", client.address());
+                emptied.push(i);
+                continue;
+            }
+        };
+
+        // Journaled before it goes out, a rerun after a crash waits on this tx instead of sending another
+        let signed = match sign_tx(&client, tx).await {
+            Ok(signed) => signed,
+            Err(e) => {
+                println!("⚠️ Signing the sweep from {:?} failed: {}", client.address(), e);
+                journal.set_status(i, JournalStatus::Failed { tx_hash: None, error: e.to_string() })?;
+                continue;
+            }
+        };
+        journal.entries[i].amount = amount;
+        journal.set_signed(&[i], &signed)?;
+        match broadcast_tx(&client, &signed, mode).await {
+            Ok(tx_hash) => {
+                println!(
+                    "✅ Sweeping {:?} → {:?} | tx: {:?} (amount: {} {})",
+                    client.address(),
+                    to,
+                    tx_hash,
+                    format_ether(amount),
+                    journal.unit()
+                );
+            }
+            Err(e) => {
+                // Stays pending: the tx may have gone out anyway, settling below decides
+                println!("⚠️ Sweep from {:?} reported {} | tx: {:?}", client.address(), e, signed.tx_hash);
             }
         }
     }
 
-    println!("✅ Loaded {} wallets from {}", all_wallets.len(), folder_path);
-    Ok(all_wallets)
+    // Nothing left to move there, the run doesn't wait on them
+    for i in emptied.into_iter().rev() {
+        journal.entries.remove(i);
+    }
+
+    // Receipts of everything just sent, all at once
+    journal.settle_pending(provider, mode, DEFAULT_RECEIPT_TIMEOUT).await
 }
-pub async fn sweep(
-    main_client: Arc<SignerMiddleware<Provider<Http>, LocalWallet>>,
+
+/// Sends each wallet's whole `token` balance to `destination` as run `run_id`. Run it before
+/// the BNB sweep, while the wallets still have gas. Reruns skip wallets already swept.
+pub async fn sweep_tokens(
+    main_client: Arc<SignerMiddleware<Provider<Http>, WalletSigner>>,
     wallet_list: Vec<WalletInfo>,
-) -> Result<()> {
+    token: Address,
+    destination: Address,
+    passphrase: &str,
+    mode: &SendMode,
+    run_id: &str,
+) -> Result<TransferSummary> {
     let provider = main_client.provider();
-    let main_address = main_client.address();
+    let token_contract = ERC20::new(token, Arc::new(provider.clone()));
 
-    for w in wallet_list {
-        // Create a wallet instance from private key
-        let wallet = w.private_key.parse::<LocalWallet>()?.with_chain_id(56u64); // BSC
-        let client = Arc::new(SignerMiddleware::new(provider.clone(), wallet));
+    let mut journal = match RunJournal::load(run_id)? {
+        Some(journal) => journal,
+        None => {
+            let mut entries = Vec::new();
+            for w in &wallet_list {
+                let balance = token_contract.balance_of(w.address).call().await?;
+                if balance.is_zero() {
+                    println!("⚠️ Wallet {:?} holds no tokens, skipping.", w.address);
+                    continue;
+                }
+                entries.push(JournalEntry::planned(w.address, destination, balance));
+            }
+            RunJournal::create(run_id, RunKind::TokenSweep, FundingMode::Transfers, Some(token), entries)?
+        }
+    };
+    if journal.token != Some(token) {
+        anyhow::bail!("Run {} does not sweep token {:?}", run_id, token);
+    }
 
-        // Get balance of current wallet
-        let balance = provider.get_balance(client.address(), None).await?;
+    // Keyed by wallet, entries for emptied wallets are dropped from the journal during the run
+    let already_done: HashSet<Address> = journal
+        .entries
+        .iter()
+        .filter(|e| matches!(e.status, JournalStatus::Done { .. }))
+        .map(|e| e.from)
+        .collect();
+    run_sweep_journal(provider, &mut journal, &wallet_list, passphrase, mode, async |client: Arc<SweepClient>| {
+        // Balance as of now, the planned amount may be out of date
+        let token_contract = ERC20::new(token, client.clone());
+        let balance = token_contract.balance_of(client.address()).call().await?;
         if balance.is_zero() {
-            println!("⚠️ Wallet {:?} is empty, skipping.", client.address());
+            return Ok(SweepStep::Drop);
+        }
+        let transfer = token_contract.transfer(destination, balance);
+        if !transfer.call().await? {
+            return Ok(SweepStep::Fail("token transfer returns false".to_string()));
+        }
+        Ok(SweepStep::Send { tx: Box::new(transfer.tx), amount: balance })
+    })
+    .await?;
+
+    // A token may return false instead of reverting, so a mined tx alone doesn't mean the tokens moved
+    for entry in journal.entries.iter_mut() {
+        let JournalStatus::Done { tx_hash, .. } = entry.status else { continue };
+        if already_done.contains(&entry.from) {
             continue;
         }
+        if token_contract.balance_of(entry.from).call().await? >= entry.amount {
+            let error = "token transfer returned false".to_string();
+            entry.status = JournalStatus::Failed { tx_hash: Some(tx_hash), error };
+        }
+    }
+    journal.save()?;
+    journal.print();
+    Ok(journal.summary())
+}
 
-        // Estimate gas fee
-        let gas_price = provider.get_gas_price().await?;
-        let gas_limit = 21_000u64; // simple BNB transfer
-        let fee = gas_price * gas_limit;
+/// Sweeps every wallet's BNB back to the main wallet as run `run_id`. A rerun with the same id
+/// skips wallets already swept, waits for pending sweeps by hash and only sends the rest.
+pub async fn sweep(
+    main_client: Arc<SignerMiddleware<Provider<Http>, WalletSigner>>,
+    wallet_list: Vec<WalletInfo>,
+    passphrase: &str,
+    mode: &SendMode,
+    tx_type: SweepTxType,
+    run_id: &str,
+) -> Result<TransferSummary> {
+    let provider = main_client.provider();
+    let main_address = main_client.address();
 
-        if balance <= fee {
-            println!("⚠️ Wallet {:?} balance too low to cover gas.", client.address());
-            continue;
+    let mut journal = match RunJournal::load(run_id)? {
+        Some(journal) => journal,
+        None => {
+            let fee = SweepGas::fetch(provider, tx_type, main_address, main_address).await?.fee();
+            let mut entries = Vec::new();
+            for w in &wallet_list {
+                let balance = provider.get_balance(w.address, None).await?;
+                if balance <= fee {
+                    println!("⚠️ Wallet {:?} has nothing to sweep, skipping.", w.address);
+                    continue;
+                }
+                entries.push(JournalEntry::planned(w.address, main_address, balance - fee));
+            }
+            RunJournal::create(run_id, RunKind::Sweep, FundingMode::Transfers, None, entries)?
         }
+    };
 
-        let sweep_amount = balance - fee;
-        let tx = TransactionRequest::pay(main_address, sweep_amount);
-
-        let pending_tx = client.send_transaction(tx, None).await?;
-        println!(
-            "✅ Sweeping {:?} → {} | tx: {:?} (amount: {} wei)",
-            client.address(),
-            main_address,
-            pending_tx.tx_hash(),
-            sweep_amount
-        );
-    }
+    run_sweep_journal(provider, &mut journal, &wallet_list, passphrase, mode, async |client: Arc<SweepClient>| {
+        // Balance as of now, the planned amount may be out of date
+        let balance = provider.get_balance(client.address(), None).await?;
+        let gas = SweepGas::fetch(provider, tx_type, client.address(), main_address).await?;
 
-    Ok(())
-}
\ No newline at end of file
+        // Gas fully specified, so the fee is exact and the wallet ends at zero
+        let chain_id = client.signer().chain_id();
+        Ok(match gas.sweep_tx(client.address(), main_address, balance, chain_id) {
+            Some(tx) => SweepStep::Send { amount: tx.value().copied().unwrap_or_default(), tx: Box::new(tx) },
+            None => SweepStep::Fail("balance too low to cover gas".to_string()),
+        })
+    })
+    .await?;
+    journal.print();
+    Ok(journal.summary())
+}
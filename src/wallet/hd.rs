@@ -0,0 +1,86 @@
+use anyhow::{Context, Result};
+use ethers::core::k256::ecdsa::SigningKey;
+use ethers::prelude::*;
+use ethers::signers::coins_bip39::{English, Mnemonic};
+use std::{fs, ops::Range, path::Path};
+use zeroize::Zeroizing;
+
+use crate::types::{KeySource, WalletInfo, WalletOrigin};
+use crate::wallet::WALLETS_DIR;
+
+/// BIP-44 Ethereum path, the wallet index is appended
+pub const HD_PATH_PREFIX: &str = "m/44'/60'/0'/0/";
+/// The mnemonic, encrypted with the wallet passphrase in the same keystore format as the keys
+pub const MNEMONIC_FILE: &str = "mnemonic.keystore";
+
+fn mnemonic_path() -> std::path::PathBuf {
+    Path::new(WALLETS_DIR).join(MNEMONIC_FILE)
+}
+
+pub fn has_mnemonic() -> bool {
+    mnemonic_path().exists()
+}
+
+/// Encrypts and stores `phrase`, refusing to replace an existing mnemonic
+pub fn import_mnemonic(phrase: &str, passphrase: &str) -> Result<()> {
+    if has_mnemonic() {
+        anyhow::bail!("{} already exists, refusing to overwrite it", mnemonic_path().display());
+    }
+    Mnemonic::<English>::new_from_phrase(phrase.trim()).context("Invalid BIP-39 mnemonic")?;
+    fs::create_dir_all(WALLETS_DIR)?;
+    eth_keystore::encrypt_key(
+        WALLETS_DIR,
+        &mut rand::thread_rng(),
+        phrase.trim().as_bytes(),
+        passphrase,
+        Some(MNEMONIC_FILE),
+    )?;
+    Ok(())
+}
+
+/// Generates and stores a new 24-word mnemonic. The phrase is returned once for backup.
+pub fn create_mnemonic(passphrase: &str) -> Result<Zeroizing<String>> {
+    let mnemonic = Mnemonic::<English>::new_with_count(&mut rand::thread_rng(), 24)?;
+    let phrase = Zeroizing::new(mnemonic.to_phrase());
+    import_mnemonic(&phrase, passphrase)?;
+    Ok(phrase)
+}
+
+pub fn load_mnemonic(passphrase: &str) -> Result<Zeroizing<String>> {
+    let bytes = eth_keystore::decrypt_key(mnemonic_path(), passphrase)
+        .with_context(|| format!("Failed to unlock {}", mnemonic_path().display()))?;
+    Ok(Zeroizing::new(String::from_utf8(bytes)?))
+}
+
+/// The wallet at `m/44'/60'/0'/0/<index>`
+pub fn derive_wallet(phrase: &str, index: u32) -> Result<LocalWallet> {
+    let mnemonic = Mnemonic::<English>::new_from_phrase(phrase)?;
+    let derived = mnemonic.derive_key(format!("{}{}", HD_PATH_PREFIX, index).as_str(), None)?;
+    let key: &SigningKey = derived.as_ref();
+    Ok(LocalWallet::from_bytes(&key.to_bytes())?)
+}
+
+/// Re-derives the wallets at `range`, for rebuilding an index from the mnemonic alone
+pub fn derive_wallet_range(phrase: &str, range: Range<u32>) -> Result<Vec<WalletInfo>> {
+    range
+        .map(|hd_index| {
+            Ok(WalletInfo::new(
+                derive_wallet(phrase, hd_index)?.address(),
+                KeySource::Derived { hd_index },
+                WalletOrigin::Derived,
+            ))
+        })
+        .collect()
+}
+
+/// First index past every derived wallet already recorded
+pub fn next_hd_index(wallets: &[WalletInfo]) -> u32 {
+    wallets
+        .iter()
+        .filter_map(|w| match w.key {
+            KeySource::Derived { hd_index } => Some(hd_index + 1),
+            KeySource::Keystore { .. } => None,
+        })
+        .max()
+        .unwrap_or(0)
+}
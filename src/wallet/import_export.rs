@@ -0,0 +1,245 @@
+use anyhow::{Context, Result};
+use chrono::Utc;
+use ethers::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashSet, fs, path::{Path, PathBuf}};
+use zeroize::Zeroizing;
+
+use crate::types::{KeySource, WalletInfo, WalletOrigin};
+use crate::wallet::{WalletRegistry, parse_wallet_key, store_wallet_key, unlock_wallets};
+
+/// One CSV row: `address,key,label`, where `key` is a hex private key or a keystore file path
+#[derive(Debug, Serialize, Deserialize)]
+struct CsvWallet {
+    address: String,
+    key: Zeroizing<String>,
+    #[serde(default)]
+    label: Option<String>,
+}
+
+/// What goes in the `key` column on export
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsvKeyColumn {
+    /// Keystore path; derived wallets get a keystore written next to the CSV
+    KeystorePath,
+    /// Raw hex private keys, in the clear
+    PrivateKey,
+}
+
+/// Result of one import run
+#[derive(Debug, Default)]
+pub struct ImportReport {
+    pub imported: Vec<WalletInfo>,
+    pub duplicates: Vec<Address>,
+    pub rejected: Vec<(String, String)>, // (row or file, reason)
+    pub batch_file: Option<PathBuf>,
+}
+
+impl ImportReport {
+    pub fn print_summary(&self) {
+        println!(
+            "📥 Imported {} | duplicates skipped {} | rejected {}",
+            self.imported.len(),
+            self.duplicates.len(),
+            self.rejected.len()
+        );
+        for (source, reason) in &self.rejected {
+            println!("   ❌ {}: {}", source, reason);
+        }
+    }
+}
+
+/// Hex keys are 32 bytes, anything else in the key column is a keystore path
+fn is_hex_key(key: &str) -> bool {
+    let key = key.trim().trim_start_matches("0x");
+    key.len() == 64 && key.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// `address` field of a keystore file, written by geth-compatible tools
+fn keystore_address(path: &Path) -> Result<Option<Address>> {
+    let json: serde_json::Value = serde_json::from_str(&fs::read_to_string(path)?)?;
+    json.get("address")
+        .and_then(|a| a.as_str())
+        .map(|a| format!("0x{}", a.trim_start_matches("0x")).parse::<Address>())
+        .transpose()
+        .context("Keystore has an invalid address field")
+}
+
+/// Decrypts a foreign keystore, checking it against `expected` and the file's own address field
+fn unlock_foreign_keystore(path: &Path, expected: Option<Address>, source_passphrase: &str) -> Result<LocalWallet> {
+    let wallet = LocalWallet::decrypt_keystore(path, source_passphrase).context("Failed to decrypt keystore")?;
+    if let Some(address) = keystore_address(path)?
+        && address != wallet.address()
+    {
+        anyhow::bail!("Keystore address field {:?} does not match its key", address);
+    }
+    if let Some(address) = expected
+        && address != wallet.address()
+    {
+        anyhow::bail!("Key belongs to {:?}, not {:?}", wallet.address(), address);
+    }
+    Ok(wallet)
+}
+
+/// Re-encrypts every accepted wallet under our passphrase and records them as one registry batch
+fn finish_import(
+    registry: &mut WalletRegistry,
+    accepted: Vec<(LocalWallet, Option<String>)>,
+    group: Option<&str>,
+    passphrase: &str,
+    report: &mut ImportReport,
+) -> Result<()> {
+    for (wallet, label) in accepted {
+        let mut info = store_wallet_key(&wallet, passphrase, WalletOrigin::Imported)?;
+        info.label = label;
+        info.group = group.map(str::to_string);
+        report.imported.push(info);
+    }
+    if !report.imported.is_empty() {
+        report.batch_file = Some(registry.add_batch(report.imported.clone())?);
+    }
+    Ok(())
+}
+
+/// Imports `address,key,label` rows. Keystore paths are unlocked with `source_passphrase`,
+/// every key is re-encrypted with `passphrase`. Invalid rows are rejected, known wallets skipped.
+pub fn import_wallets_csv(
+    path: &Path,
+    group: Option<&str>,
+    source_passphrase: &str,
+    passphrase: &str,
+) -> Result<ImportReport> {
+    let mut registry = WalletRegistry::load()?;
+    let mut report = ImportReport::default();
+    let mut seen: HashSet<Address> = registry.wallets().map(|w| w.address).collect();
+    let mut accepted = Vec::new();
+    let base_dir = path.parent().unwrap_or(Path::new("."));
+
+    let mut reader = csv::Reader::from_path(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    for (i, row) in reader.deserialize::<CsvWallet>().enumerate() {
+        let source = format!("row {}", i + 2); // 1-based, after the header
+        let row = match row {
+            Ok(row) => row,
+            Err(e) => {
+                report.rejected.push((source, e.to_string()));
+                continue;
+            }
+        };
+
+        let wallet = row
+            .address
+            .trim()
+            .parse::<Address>()
+            .context("Invalid address")
+            .and_then(|address| {
+                if is_hex_key(&row.key) {
+                    parse_wallet_key(address, &row.key)
+                } else {
+                    unlock_foreign_keystore(&base_dir.join(row.key.trim()), Some(address), source_passphrase)
+                }
+            });
+        match wallet {
+            Ok(wallet) if !seen.insert(wallet.address()) => report.duplicates.push(wallet.address()),
+            Ok(wallet) => accepted.push((wallet, row.label.filter(|l| !l.is_empty()))),
+            Err(e) => report.rejected.push((source, format!("{:#}", e))),
+        }
+    }
+
+    finish_import(&mut registry, accepted, group, passphrase, &mut report)?;
+    Ok(report)
+}
+
+/// Imports every keystore file in `dir`, unlocked with `source_passphrase`
+pub fn import_keystore_dir(
+    dir: &Path,
+    group: Option<&str>,
+    source_passphrase: &str,
+    passphrase: &str,
+) -> Result<ImportReport> {
+    let mut registry = WalletRegistry::load()?;
+    let mut report = ImportReport::default();
+    let mut seen: HashSet<Address> = registry.wallets().map(|w| w.address).collect();
+    let mut accepted = Vec::new();
+
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<Result<_, _>>()?;
+    paths.sort();
+    for path in paths {
+        let hidden = path.file_name().and_then(|n| n.to_str()).is_none_or(|n| n.starts_with('.'));
+        if !path.is_file() || hidden {
+            continue;
+        }
+        match unlock_foreign_keystore(&path, None, source_passphrase) {
+            Ok(wallet) if !seen.insert(wallet.address()) => report.duplicates.push(wallet.address()),
+            Ok(wallet) => accepted.push((wallet, None)),
+            Err(e) => report.rejected.push((path.display().to_string(), format!("{:#}", e))),
+        }
+    }
+
+    finish_import(&mut registry, accepted, group, passphrase, &mut report)?;
+    Ok(report)
+}
+
+/// Writes `wallet` as a geth-style `UTC--<time>--<address>` keystore in `dir`
+fn write_standard_keystore(dir: &Path, wallet: &LocalWallet, passphrase: &str) -> Result<PathBuf> {
+    let address = hex::encode(wallet.address());
+    let name = format!("UTC--{}--{}", Utc::now().format("%Y-%m-%dT%H-%M-%S%.9fZ"), address);
+    let key = Zeroizing::new(wallet.signer().to_bytes().to_vec());
+    LocalWallet::encrypt_keystore(dir, &mut rand::thread_rng(), &*key, passphrase, Some(&name))?;
+
+    // Other tools look the wallet up by the address field
+    let path = dir.join(&name);
+    let mut json: serde_json::Value = serde_json::from_str(&fs::read_to_string(&path)?)?;
+    json["address"] = serde_json::Value::String(address);
+    fs::write(&path, serde_json::to_string(&json)?)?;
+    Ok(path)
+}
+
+/// Writes every wallet into `dir` as standard keystore files encrypted with `passphrase`
+pub fn export_keystore_dir(dir: &Path, wallet_list: &[WalletInfo], passphrase: &str) -> Result<usize> {
+    fs::create_dir_all(dir)?;
+    for wallet in unlock_wallets(wallet_list, passphrase)? {
+        write_standard_keystore(dir, &wallet, passphrase)?;
+    }
+    println!("📤 Exported {} keystores to {}", wallet_list.len(), dir.display());
+    Ok(wallet_list.len())
+}
+
+/// Writes `address,key,label` rows to `path`
+pub fn export_wallets_csv(
+    path: &Path,
+    wallet_list: &[WalletInfo],
+    passphrase: &str,
+    column: CsvKeyColumn,
+) -> Result<usize> {
+    let wallets = unlock_wallets(wallet_list, passphrase)?;
+    let keystore_dir = path.with_extension("keystore");
+    let mut writer = csv::Writer::from_path(path)?;
+
+    for (info, wallet) in wallet_list.iter().zip(&wallets) {
+        let key = match (column, &info.key) {
+            (CsvKeyColumn::PrivateKey, _) => Zeroizing::new(format!("0x{}", hex::encode(wallet.signer().to_bytes()))),
+            (CsvKeyColumn::KeystorePath, KeySource::Keystore { keystore }) => {
+                Zeroizing::new(fs::canonicalize(keystore)?.display().to_string())
+            }
+            (CsvKeyColumn::KeystorePath, KeySource::Derived { .. }) => {
+                fs::create_dir_all(&keystore_dir)?;
+                let keystore = write_standard_keystore(&keystore_dir, wallet, passphrase)?;
+                Zeroizing::new(fs::canonicalize(keystore)?.display().to_string())
+            }
+        };
+        writer.serialize(CsvWallet {
+            address: format!("{:?}", info.address),
+            key,
+            label: info.label.clone(),
+        })?;
+    }
+    writer.flush()?;
+
+    if column == CsvKeyColumn::PrivateKey {
+        println!("⚠️ {} holds plaintext private keys, delete it once imported", path.display());
+    }
+    println!("📤 Exported {} wallets to {}", wallet_list.len(), path.display());
+    Ok(wallet_list.len())
+}
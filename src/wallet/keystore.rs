@@ -0,0 +1,140 @@
+use anyhow::{Context, Result};
+use ethers::prelude::*;
+use serde::Deserialize;
+use std::{env, fs, path::Path};
+use zeroize::Zeroizing;
+
+use crate::types::{KeySource, WalletInfo, WalletOrigin};
+use crate::wallet::{derive_wallet, load_mnemonic, write_wallet_index};
+
+/// Wallet index files (`<timestamp>.json`) live here, their keystores one level down
+pub const WALLETS_DIR: &str = "src/wallets";
+pub const KEYSTORE_DIR: &str = "src/wallets/keystore";
+
+/// Old `gen_several_wallets` output, raw hex keys in the clear
+#[derive(Deserialize)]
+struct PlaintextWallet {
+    address: Address,
+    private_key: Zeroizing<String>,
+}
+
+/// `WALLET_PASSPHRASE` if set, otherwise prompted on the terminal
+pub fn wallet_passphrase() -> Result<Zeroizing<String>> {
+    dotenvy::dotenv().ok();
+    if let Ok(passphrase) = env::var("WALLET_PASSPHRASE") {
+        return Ok(Zeroizing::new(passphrase));
+    }
+    let passphrase = rpassword::prompt_password("🔑 Wallet passphrase: ")?;
+    Ok(Zeroizing::new(passphrase))
+}
+
+/// Generates a fresh key straight into a scrypt/AES keystore under `KEYSTORE_DIR`
+pub fn new_keystore_wallet(passphrase: &str) -> Result<WalletInfo> {
+    fs::create_dir_all(KEYSTORE_DIR)?;
+    let (wallet, uuid) = LocalWallet::new_keystore(KEYSTORE_DIR, &mut rand::thread_rng(), passphrase, None)?;
+    Ok(WalletInfo::new(
+        wallet.address(),
+        KeySource::Keystore { keystore: Path::new(KEYSTORE_DIR).join(uuid) },
+        WalletOrigin::Generated,
+    ))
+}
+
+/// Parses a hex private key, checking it belongs to `address`
+pub fn parse_wallet_key(address: Address, private_key: &str) -> Result<LocalWallet> {
+    let key = Zeroizing::new(hex::decode(private_key.trim().trim_start_matches("0x")).context("Private key is not hex")?);
+    let wallet = LocalWallet::from_bytes(&key).context("Not a valid secp256k1 private key")?;
+    if wallet.address() != address {
+        anyhow::bail!("Private key does not belong to {:?}", address);
+    }
+    Ok(wallet)
+}
+
+/// Encrypts an unlocked wallet's key into a new keystore under `KEYSTORE_DIR`
+pub fn store_wallet_key(wallet: &LocalWallet, passphrase: &str, origin: WalletOrigin) -> Result<WalletInfo> {
+    let key = Zeroizing::new(wallet.signer().to_bytes().to_vec());
+    fs::create_dir_all(KEYSTORE_DIR)?;
+    let (_, uuid) = LocalWallet::encrypt_keystore(KEYSTORE_DIR, &mut rand::thread_rng(), &*key, passphrase, None)?;
+    let keystore = Path::new(KEYSTORE_DIR).join(uuid);
+    Ok(WalletInfo::new(wallet.address(), KeySource::Keystore { keystore }, origin))
+}
+
+/// Encrypts an existing raw key into a keystore, checking it belongs to `address`
+pub fn encrypt_wallet_key(
+    address: Address,
+    private_key: &str,
+    passphrase: &str,
+    origin: WalletOrigin,
+) -> Result<WalletInfo> {
+    store_wallet_key(&parse_wallet_key(address, private_key)?, passphrase, origin)
+}
+
+/// Decrypts the wallet's keystore, or re-derives it from the stored mnemonic.
+/// The key is zeroized when the `LocalWallet` drops.
+pub fn unlock_wallet(info: &WalletInfo, passphrase: &str) -> Result<LocalWallet> {
+    let wallet = match &info.key {
+        KeySource::Keystore { keystore } => LocalWallet::decrypt_keystore(keystore, passphrase)
+            .with_context(|| format!("Failed to unlock keystore {}", keystore.display()))?,
+        KeySource::Derived { hd_index } => derive_wallet(&load_mnemonic(passphrase)?, *hd_index)?,
+    };
+    if wallet.address() != info.address {
+        anyhow::bail!("Key for {:?} unlocked to a different address", info.address);
+    }
+    Ok(wallet)
+}
+
+/// `unlock_wallet` for a whole set, decrypting the mnemonic at most once
+pub fn unlock_wallets(wallet_list: &[WalletInfo], passphrase: &str) -> Result<Vec<LocalWallet>> {
+    let mut mnemonic = None;
+    let mut wallets = Vec::with_capacity(wallet_list.len());
+    for info in wallet_list {
+        let wallet = match &info.key {
+            KeySource::Keystore { .. } => unlock_wallet(info, passphrase)?,
+            KeySource::Derived { hd_index } => {
+                let phrase = match &mut mnemonic {
+                    Some(phrase) => phrase,
+                    slot @ None => slot.insert(load_mnemonic(passphrase)?),
+                };
+                derive_wallet(phrase.as_str(), *hd_index)?
+            }
+        };
+        if wallet.address() != info.address {
+            anyhow::bail!("Key for {:?} unlocked to a different address", info.address);
+        }
+        wallets.push(wallet);
+    }
+    Ok(wallets)
+}
+
+/// Rewrites every plaintext wallet file under `WALLETS_DIR` as keystores plus an index.
+/// Returns how many keys were migrated.
+pub fn migrate_plaintext_wallets(passphrase: &str) -> Result<usize> {
+    let mut migrated = 0;
+    if !Path::new(WALLETS_DIR).exists() {
+        return Ok(migrated);
+    }
+
+    for entry in fs::read_dir(WALLETS_DIR)? {
+        let path = entry?.path();
+        if !path.is_file() || path.extension().is_none_or(|ext| ext != "json") {
+            continue;
+        }
+
+        let content = Zeroizing::new(fs::read_to_string(&path)?);
+        // Already an index of keystores (or something else entirely)
+        let Ok(plaintext) = serde_json::from_str::<Vec<PlaintextWallet>>(&content) else {
+            continue;
+        };
+
+        let mut wallet_list = Vec::with_capacity(plaintext.len());
+        for w in &plaintext {
+            wallet_list.push(encrypt_wallet_key(w.address, &w.private_key, passphrase, WalletOrigin::Generated)?);
+        }
+
+        write_wallet_index(&path, &wallet_list)?;
+
+        println!("🔐 Migrated {} wallets in {}", wallet_list.len(), path.display());
+        migrated += wallet_list.len();
+    }
+
+    Ok(migrated)
+}
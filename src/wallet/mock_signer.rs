@@ -0,0 +1,98 @@
+use anyhow::Result;
+use ethers::prelude::*;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use serde_json::{Value, json};
+use std::net::SocketAddr;
+use tokio::io::BufReader;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+
+use crate::bundle::mock::{read_http_request, rpc_error, write_http_json};
+use crate::wallet::RemoteSigner;
+
+/// Local stand-in for a Web3Signer-compatible remote signer holding one key.
+/// Speaks `eth_accounts`, `eth_sign` and `eth_signTransaction`.
+pub struct MockRemoteSigner {
+    addr: SocketAddr,
+    address: Address,
+    server: JoinHandle<()>,
+}
+
+impl MockRemoteSigner {
+    /// Starts listening on a random local port
+    pub async fn start(wallet: LocalWallet) -> Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let address = wallet.address();
+
+        let server = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let wallet = wallet.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = handle_connection(stream, wallet).await {
+                        println!("⚠️ Mock signer connection error: {}", e);
+                    }
+                });
+            }
+        });
+
+        Ok(Self { addr, address, server })
+    }
+
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// A `RemoteSigner` pointed at this stand-in
+    pub fn signer(&self) -> RemoteSigner {
+        RemoteSigner::new(&self.url(), self.address, None)
+    }
+}
+
+impl Drop for MockRemoteSigner {
+    fn drop(&mut self) {
+        self.server.abort();
+    }
+}
+
+async fn handle_connection(stream: TcpStream, wallet: LocalWallet) -> Result<()> {
+    let mut reader = BufReader::new(stream);
+    let request = read_http_request(&mut reader).await?;
+    let (status, response) = match serde_json::from_slice::<Value>(&request.body) {
+        Ok(request) => ("200 OK", handle_rpc(&wallet, request).await),
+        Err(e) => ("400 Bad Request", rpc_error(Value::Null, -32700, &e.to_string())),
+    };
+    write_http_json(reader.get_mut(), status, &response).await
+}
+
+async fn handle_rpc(wallet: &LocalWallet, request: Value) -> Value {
+    let id = request["id"].clone();
+    let params = &request["params"];
+    let result: Result<Value> = async {
+        match request["method"].as_str().unwrap_or_default() {
+            "eth_accounts" => Ok(json!([wallet.address()])),
+            "eth_sign" => {
+                let message: Bytes = serde_json::from_value(params[1].clone())?;
+                let signature = wallet.sign_message(message.as_ref()).await?;
+                Ok(json!(format!("0x{}", signature)))
+            }
+            "eth_signTransaction" => {
+                let mut tx: TypedTransaction = serde_json::from_value(params[0].clone())?;
+                if tx.from() != Some(&wallet.address()) {
+                    anyhow::bail!("Unknown account {:?}", tx.from());
+                }
+                let chain_id: U64 = serde_json::from_value(params[0]["chainId"].clone())?;
+                tx.set_chain_id(chain_id);
+                let signature = wallet.sign_transaction(&tx).await?;
+                Ok(json!(tx.rlp_signed(&signature)))
+            }
+            method => anyhow::bail!("Method {} not supported", method),
+        }
+    }
+    .await;
+
+    match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(e) => rpc_error(id, -32000, &e.to_string()),
+    }
+}
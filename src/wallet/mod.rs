@@ -0,0 +1,16 @@
+pub mod keystore;
+pub mod hd;
+pub mod registry;
+pub mod import_export;
+pub mod signer;
+#[cfg(any(test, feature = "mock"))]
+pub mod mock_signer;
+
+
+pub use keystore::*;
+pub use hd::*;
+pub use registry::*;
+pub use import_export::*;
+pub use signer::*;
+#[cfg(any(test, feature = "mock"))]
+pub use mock_signer::*;
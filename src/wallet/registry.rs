@@ -0,0 +1,185 @@
+use anyhow::{Context, Result};
+use ethers::types::Address;
+use std::{collections::{BTreeMap, HashSet}, fs, path::{Path, PathBuf}, time::{SystemTime, UNIX_EPOCH}};
+
+use crate::types::{KeySource, WalletInfo, WalletOrigin};
+use crate::wallet::WALLETS_DIR;
+
+/// Writes a wallet index file, write-then-rename so a crash never leaves it half-written
+pub fn write_wallet_index(path: &Path, wallets: &[WalletInfo]) -> Result<()> {
+    let tmp_path = path.with_extension("json.tmp");
+    fs::write(&tmp_path, serde_json::to_string_pretty(wallets)?)?;
+    fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
+/// Every wallet under `WALLETS_DIR`, grouped by the batch file it lives in.
+/// Edits are written back to the file the wallet came from.
+#[derive(Debug, Default)]
+pub struct WalletRegistry {
+    batches: BTreeMap<PathBuf, Vec<WalletInfo>>,
+}
+
+/// Age of a batch file: the timestamp it's named by (`<ts>.json`, `<ts>-<n>.json`), else its
+/// oldest wallet, then `n` for batches written in the same second
+fn batch_order(path: &Path, wallets: &[WalletInfo]) -> (u64, u32, PathBuf) {
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or_default();
+    let (time, index) = stem.split_once('-').unwrap_or((stem, "0"));
+    let time = time
+        .parse::<u64>()
+        .unwrap_or_else(|_| wallets.iter().map(|w| w.created_at).min().unwrap_or_default());
+    (time, index.parse().unwrap_or_default(), path.to_path_buf())
+}
+
+impl WalletRegistry {
+    pub fn load() -> Result<Self> {
+        let mut registry = Self::default();
+        if !Path::new(WALLETS_DIR).exists() {
+            return Ok(registry);
+        }
+
+        for entry in fs::read_dir(WALLETS_DIR)? {
+            let path = entry?.path();
+            if !path.is_file() || path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+            let content = fs::read_to_string(&path)?;
+            let mut wallets: Vec<WalletInfo> = serde_json::from_str(&content).with_context(|| {
+                format!("{} is not a keystore index, migrate plaintext wallets first", path.display())
+            })?;
+
+            // Files from before the registry: batch files are named by their unix timestamp
+            let batch_time = path.file_stem().and_then(|s| s.to_str()?.parse::<u64>().ok());
+            for w in &mut wallets {
+                if w.created_at == 0 {
+                    w.created_at = batch_time.unwrap_or_default();
+                }
+                if matches!(w.key, KeySource::Derived { .. }) {
+                    w.origin = WalletOrigin::Derived;
+                }
+            }
+            registry.batches.insert(path, wallets);
+        }
+
+        // A wallet listed in two batch files is kept only in the oldest one. Paths don't sort
+        // by age ("<ts>-1.json" sorts before "<ts>.json"), so go by batch time, then index.
+        let mut by_age: Vec<(u64, u32, PathBuf)> =
+            registry.batches.iter().map(|(path, wallets)| batch_order(path, wallets)).collect();
+        by_age.sort();
+        let mut seen = HashSet::new();
+        for (_, _, path) in by_age {
+            let wallets = registry.batches.get_mut(&path).expect("key from the same map");
+            wallets.retain(|w| {
+                let first = seen.insert(w.address);
+                if !first {
+                    println!("⚠️ {:?} is listed again in {}, ignoring the duplicate", w.address, path.display());
+                }
+                first
+            });
+        }
+        Ok(registry)
+    }
+
+    /// Records `wallets` as a new timestamped batch file
+    pub fn add_batch(&mut self, wallets: Vec<WalletInfo>) -> Result<PathBuf> {
+        fs::create_dir_all(WALLETS_DIR)?;
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        let mut path = Path::new(WALLETS_DIR).join(format!("{}.json", timestamp));
+        // Two batches in the same second must not overwrite each other
+        let mut n = 1;
+        while path.exists() || self.batches.contains_key(&path) {
+            path = Path::new(WALLETS_DIR).join(format!("{}-{}.json", timestamp, n));
+            n += 1;
+        }
+        write_wallet_index(&path, &wallets)?;
+        self.batches.insert(path.clone(), wallets);
+        Ok(path)
+    }
+
+    /// Writes every batch back to disk
+    pub fn save(&self) -> Result<()> {
+        for (path, wallets) in &self.batches {
+            write_wallet_index(path, wallets)?;
+        }
+        Ok(())
+    }
+
+    pub fn wallets(&self) -> impl Iterator<Item = &WalletInfo> {
+        self.batches.values().flatten()
+    }
+
+    pub fn len(&self) -> usize {
+        self.wallets().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn into_wallets(self) -> Vec<WalletInfo> {
+        self.batches.into_values().flatten().collect()
+    }
+
+    pub fn get(&self, address: Address) -> Option<&WalletInfo> {
+        self.wallets().find(|w| w.address == address)
+    }
+
+    pub fn get_mut(&mut self, address: Address) -> Option<&mut WalletInfo> {
+        self.batches.values_mut().flatten().find(|w| w.address == address)
+    }
+
+    /// Wallets that haven't been retired
+    pub fn active(&self) -> Vec<&WalletInfo> {
+        self.wallets().filter(|w| !w.retired).collect()
+    }
+
+    /// Active wallets in `group`, e.g. "launch-42"
+    pub fn in_group(&self, group: &str) -> Vec<&WalletInfo> {
+        self.wallets()
+            .filter(|w| !w.retired && w.group.as_deref() == Some(group))
+            .collect()
+    }
+
+    pub fn with_label(&self, label: &str) -> Vec<&WalletInfo> {
+        self.wallets().filter(|w| w.label.as_deref() == Some(label)).collect()
+    }
+
+    pub fn with_origin(&self, origin: WalletOrigin) -> Vec<&WalletInfo> {
+        self.wallets().filter(|w| w.origin == origin).collect()
+    }
+
+    /// Active wallet count per group
+    pub fn groups(&self) -> BTreeMap<String, usize> {
+        let mut groups = BTreeMap::new();
+        for w in self.active() {
+            if let Some(group) = &w.group {
+                *groups.entry(group.clone()).or_default() += 1;
+            }
+        }
+        groups
+    }
+
+    /// Moves every wallet in `addresses` into `group`. Returns how many were found.
+    pub fn assign_group(&mut self, addresses: &[Address], group: &str) -> usize {
+        let mut found = 0;
+        for w in self.batches.values_mut().flatten() {
+            if addresses.contains(&w.address) {
+                w.group = Some(group.to_string());
+                found += 1;
+            }
+        }
+        found
+    }
+
+    /// Keeps the wallet on record but out of `active` and `in_group`
+    pub fn retire(&mut self, address: Address, notes: Option<&str>) -> Result<()> {
+        let wallet = self
+            .get_mut(address)
+            .with_context(|| format!("{:?} is not in the wallet registry", address))?;
+        wallet.retired = true;
+        if let Some(notes) = notes {
+            wallet.notes = Some(notes.to_string());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,261 @@
+use anyhow::Context;
+use async_trait::async_trait;
+use ethers::prelude::*;
+use ethers::types::transaction::{eip2718::TypedTransaction, eip712::Eip712};
+use ethers::utils::rlp::Rlp;
+use reqwest::Client;
+use std::{env, path::Path};
+
+use crate::bundle::types::{JsonRpcRequest, JsonRpcResponse};
+use crate::wallet::wallet_passphrase;
+
+#[derive(Debug, thiserror::Error)]
+pub enum SignerError {
+    #[error(transparent)]
+    Wallet(#[from] WalletError),
+    #[error("remote signer request failed: {0}")]
+    Transport(#[from] reqwest::Error),
+    #[error("remote signer rejected the request with code {code}: {message}")]
+    Rpc { code: i64, message: String },
+    #[error("failed to encode tx for the remote signer: {0}")]
+    Encode(#[from] serde_json::Error),
+    #[error("remote signer returned an invalid response: {0}")]
+    InvalidResponse(String),
+    #[error("remote signer signature does not recover to {0:?}")]
+    WrongSignature(Address),
+    #[error("remote signer can't sign {0}")]
+    Unsupported(&'static str),
+}
+
+/// Signs over JSON-RPC with the Web3Signer eth1 API (`eth_sign`, `eth_signTransaction`).
+/// The key never leaves the signer; every signature is checked against `address` here.
+#[derive(Debug, Clone)]
+pub struct RemoteSigner {
+    client: Client,
+    url: String,
+    auth: Option<String>,
+    address: Address,
+    chain_id: u64,
+}
+
+impl RemoteSigner {
+    pub fn new(url: &str, address: Address, auth: Option<String>) -> Self {
+        Self {
+            client: Client::new(),
+            url: url.to_string(),
+            auth,
+            address,
+            chain_id: 1,
+        }
+    }
+
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    async fn call<P: serde::Serialize + Sync, R: serde::de::DeserializeOwned>(
+        &self,
+        method: &str,
+        params: P,
+    ) -> Result<R, SignerError> {
+        let mut req = self.client.post(&self.url).json(&JsonRpcRequest::new(method, params));
+        if let Some(auth) = &self.auth {
+            req = req.header("Authorization", auth);
+        }
+        let res = req.send().await?;
+        let status = res.status();
+        let text = res.text().await?;
+        let body: JsonRpcResponse<R> = serde_json::from_str(&text)
+            .map_err(|_| SignerError::InvalidResponse(format!("HTTP {}: {}", status, text)))?;
+        match (body.result, body.error) {
+            (_, Some(error)) => Err(SignerError::Rpc { code: error.code, message: error.message }),
+            (Some(result), None) => Ok(result),
+            (None, None) => Err(SignerError::InvalidResponse(text)),
+        }
+    }
+}
+
+/// r, s and y-parity of a signed raw tx of any type, with `v` in the EIP-155 form `LocalWallet` returns
+fn raw_tx_signature(raw: &[u8], chain_id: u64) -> Option<Signature> {
+    let payload = match raw.first()? {
+        0x01 | 0x02 => &raw[1..], // typed tx envelope
+        _ => raw,
+    };
+    let rlp = Rlp::new(payload);
+    let fields = rlp.item_count().ok()?;
+    if fields < 3 {
+        return None;
+    }
+    let v: u64 = rlp.val_at(fields - 3).ok()?;
+    let parity = match v {
+        0 | 1 => v,
+        27 | 28 => v - 27,
+        v if v >= 35 => (v - 35) % 2,
+        _ => return None,
+    };
+    Some(Signature {
+        r: rlp.val_at(fields - 2).ok()?,
+        s: rlp.val_at(fields - 1).ok()?,
+        v: parity + 35 + 2 * chain_id,
+    })
+}
+
+#[async_trait]
+impl Signer for RemoteSigner {
+    type Error = SignerError;
+
+    async fn sign_message<S: Send + Sync + AsRef<[u8]>>(&self, message: S) -> Result<Signature, Self::Error> {
+        let message = message.as_ref();
+        let hex_sig: String = self
+            .call("eth_sign", (self.address, Bytes::from(message.to_vec())))
+            .await?;
+        let signature: Signature = hex_sig
+            .parse()
+            .map_err(|_| SignerError::InvalidResponse(hex_sig.clone()))?;
+        signature
+            .verify(message, self.address)
+            .map_err(|_| SignerError::WrongSignature(self.address))?;
+        Ok(signature)
+    }
+
+    async fn sign_transaction(&self, tx: &TypedTransaction) -> Result<Signature, Self::Error> {
+        let mut tx = tx.clone();
+        tx.set_from(self.address);
+        if tx.chain_id().is_none() {
+            tx.set_chain_id(self.chain_id);
+        }
+
+        let chain_id = tx.chain_id().map(|id| id.as_u64()).unwrap_or(self.chain_id);
+
+        // ethers drops `chainId` when serializing requests, Web3Signer needs it
+        let mut params = serde_json::to_value(&tx)?;
+        params["chainId"] = serde_json::to_value(U64::from(chain_id))?;
+
+        // Web3Signer answers with the signed raw tx, the signature must be over our exact sighash
+        let raw: Bytes = self.call("eth_signTransaction", [params]).await?;
+        let signature = raw_tx_signature(&raw, chain_id)
+            .ok_or_else(|| SignerError::InvalidResponse(raw.to_string()))?;
+        signature
+            .verify(tx.sighash(), self.address)
+            .map_err(|_| SignerError::WrongSignature(self.address))?;
+        Ok(signature)
+    }
+
+    async fn sign_typed_data<T: Eip712 + Send + Sync>(&self, _payload: &T) -> Result<Signature, Self::Error> {
+        Err(SignerError::Unsupported("EIP-712 typed data"))
+    }
+
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    fn chain_id(&self) -> u64 {
+        self.chain_id
+    }
+
+    fn with_chain_id<T: Into<u64>>(mut self, chain_id: T) -> Self {
+        self.chain_id = chain_id.into();
+        self
+    }
+}
+
+/// The signer behind a funding or trading wallet: a local key (plain or from a keystore) or a remote signer
+#[derive(Debug, Clone)]
+pub enum WalletSigner {
+    Local(LocalWallet),
+    Remote(RemoteSigner),
+}
+
+impl From<LocalWallet> for WalletSigner {
+    fn from(wallet: LocalWallet) -> Self {
+        WalletSigner::Local(wallet)
+    }
+}
+
+impl From<RemoteSigner> for WalletSigner {
+    fn from(signer: RemoteSigner) -> Self {
+        WalletSigner::Remote(signer)
+    }
+}
+
+impl WalletSigner {
+    /// `TREASURY_SIGNER` = key (default, `PRIVATE_KEY`) | keystore (`TREASURY_KEYSTORE`,
+    /// unlocked with the wallet passphrase) | remote (`REMOTE_SIGNER_URL`, `REMOTE_SIGNER_ADDRESS`,
+    /// optional `REMOTE_SIGNER_AUTH_HEADER`)
+    pub fn from_env() -> anyhow::Result<Self> {
+        dotenvy::dotenv().ok();
+        let kind = env::var("TREASURY_SIGNER").unwrap_or_else(|_| "key".to_string());
+        Ok(match kind.trim().to_ascii_lowercase().as_str() {
+            "key" => {
+                let private_key = env::var("PRIVATE_KEY").context("Missing PRIVATE_KEY in environment (.env)")?;
+                let wallet = private_key
+                    .parse::<LocalWallet>()
+                    .context("Failed to parse PRIVATE_KEY as LocalWallet")?;
+                WalletSigner::Local(wallet)
+            }
+            "keystore" => {
+                let path = env::var("TREASURY_KEYSTORE").context("Missing TREASURY_KEYSTORE in environment (.env)")?;
+                let passphrase = wallet_passphrase()?;
+                let wallet = LocalWallet::decrypt_keystore(Path::new(&path), passphrase.as_str())
+                    .with_context(|| format!("Failed to unlock keystore {}", path))?;
+                WalletSigner::Local(wallet)
+            }
+            "remote" => {
+                let url = env::var("REMOTE_SIGNER_URL").context("Missing REMOTE_SIGNER_URL in environment (.env)")?;
+                let address = env::var("REMOTE_SIGNER_ADDRESS")
+                    .context("Missing REMOTE_SIGNER_ADDRESS in environment (.env)")?
+                    .parse()?;
+                WalletSigner::Remote(RemoteSigner::new(&url, address, env::var("REMOTE_SIGNER_AUTH_HEADER").ok()))
+            }
+            other => anyhow::bail!("Unknown TREASURY_SIGNER: {}", other),
+        })
+    }
+}
+
+#[async_trait]
+impl Signer for WalletSigner {
+    type Error = SignerError;
+
+    async fn sign_message<S: Send + Sync + AsRef<[u8]>>(&self, message: S) -> Result<Signature, Self::Error> {
+        match self {
+            WalletSigner::Local(wallet) => Ok(wallet.sign_message(message).await?),
+            WalletSigner::Remote(signer) => signer.sign_message(message).await,
+        }
+    }
+
+    async fn sign_transaction(&self, tx: &TypedTransaction) -> Result<Signature, Self::Error> {
+        match self {
+            WalletSigner::Local(wallet) => Ok(wallet.sign_transaction(tx).await?),
+            WalletSigner::Remote(signer) => signer.sign_transaction(tx).await,
+        }
+    }
+
+    async fn sign_typed_data<T: Eip712 + Send + Sync>(&self, payload: &T) -> Result<Signature, Self::Error> {
+        match self {
+            WalletSigner::Local(wallet) => Ok(wallet.sign_typed_data(payload).await?),
+            WalletSigner::Remote(signer) => signer.sign_typed_data(payload).await,
+        }
+    }
+
+    fn address(&self) -> Address {
+        match self {
+            WalletSigner::Local(wallet) => wallet.address(),
+            WalletSigner::Remote(signer) => signer.address(),
+        }
+    }
+
+    fn chain_id(&self) -> u64 {
+        match self {
+            WalletSigner::Local(wallet) => wallet.chain_id(),
+            WalletSigner::Remote(signer) => signer.chain_id(),
+        }
+    }
+
+    fn with_chain_id<T: Into<u64>>(self, chain_id: T) -> Self {
+        match self {
+            WalletSigner::Local(wallet) => WalletSigner::Local(wallet.with_chain_id(chain_id)),
+            WalletSigner::Remote(signer) => WalletSigner::Remote(signer.with_chain_id(chain_id)),
+        }
+    }
+}
@@ -0,0 +1,115 @@
+//! Signed bundles from `BundleBuilder` against an in-memory `MockNode`
+
+use ethers::prelude::*;
+use ethers::utils::rlp;
+use std::{sync::Arc, time::Duration};
+use test_blox::bundle::*;
+
+async fn funded(node: &MockNode) -> anyhow::Result<(Arc<Provider<Http>>, LocalWallet)> {
+    node.set_automine(false);
+    let provider = Arc::new(Provider::<Http>::try_from(node.url())?.interval(Duration::from_millis(10)));
+    let wallet = LocalWallet::new(&mut rand::thread_rng());
+    node.set_balance(wallet.address(), U256::exp10(18));
+    Ok((provider, wallet))
+}
+
+fn decode(raw_tx: &str) -> anyhow::Result<Transaction> {
+    let mut tx: Transaction = rlp::decode(&hex::decode(raw_tx.trim_start_matches("0x"))?)?;
+    tx.recover_from_mut()?;
+    Ok(tx)
+}
+
+#[tokio::test]
+async fn allowed_revert_hashes_come_from_the_signed_payloads() -> anyhow::Result<()> {
+    let node = MockNode::start(56).await?;
+    let (provider, wallet) = funded(&node).await?;
+    let mut builder = BundleBuilder::new(provider.clone());
+    builder.push(TransactionRequest::pay(Address::random(), 1_000u64).into(), wallet.clone());
+    builder.push_allow_revert(TransactionRequest::pay(Address::random(), 1_000u64).into(), wallet);
+
+    let signed = builder.build().await?;
+
+    let optional = signed_tx_hash(&signed.txs[1])?;
+    assert_eq!(signed.reverting_tx_hashes, vec![optional]);
+    assert_eq!(signed.request(1).reverting_tx_hashes, vec![optional]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn bundle_lands_when_only_an_allowed_revert_tx_fails() -> anyhow::Result<()> {
+    let node = MockNode::start(56).await?;
+    let (provider, wallet) = funded(&node).await?;
+    let optional_target = Address::random();
+    node.revert_calls_to(optional_target);
+    let mut builder = BundleBuilder::new(provider.clone());
+    builder.push(TransactionRequest::pay(Address::random(), 1_000u64).into(), wallet.clone());
+    builder.push_allow_revert(TransactionRequest::pay(optional_target, 1_000u64).into(), wallet);
+
+    let signed = builder.build().await?;
+    for tx in &signed.txs {
+        provider.send_raw_transaction(Bytes::from(hex::decode(tx.trim_start_matches("0x"))?)).await?;
+    }
+    node.mine();
+
+    let tracker = signed.tracker(provider, 1)?.poll_interval(Duration::from_millis(10));
+    assert_eq!(tracker.check_block(1).await?, BundleStatus::Included { block: 1 });
+    Ok(())
+}
+
+#[tokio::test]
+async fn last_tip_follows_the_senders_bundle_nonces() -> anyhow::Result<()> {
+    let node = MockNode::start(56).await?;
+    let (provider, wallet) = funded(&node).await?;
+    // Already waiting in the mempool, the bundle has to start after it
+    let approve = TransactionRequest::pay(Address::random(), 1u64).nonce(0).gas(21_000u64).gas_price(U256::exp10(9)).chain_id(56u64);
+    let signature = wallet.clone().with_chain_id(56u64).sign_transaction(&approve.clone().into()).await?;
+    provider.send_raw_transaction(approve.rlp_signed(&signature)).await?;
+
+    let builder_address = Address::random();
+    let mut builder = BundleBuilder::new(provider.clone());
+    builder.push(TransactionRequest::pay(Address::random(), 1_000u64).into(), wallet.clone());
+    builder.push(TransactionRequest::pay(Address::random(), 1_000u64).into(), wallet.clone());
+    builder.tip(BuilderTip {
+        to: builder_address,
+        signer: wallet.clone().into(),
+        amount: TipAmount::Fixed(U256::exp10(15)),
+        placement: TipPlacement::Last,
+    });
+
+    let signed = builder.build().await?;
+
+    let txs = signed.txs.iter().map(|tx| decode(tx)).collect::<anyhow::Result<Vec<_>>>()?;
+    assert_eq!(txs.iter().map(|tx| tx.nonce.as_u64()).collect::<Vec<_>>(), vec![1, 2, 3]);
+    let tip = txs.last().unwrap();
+    assert_eq!((tip.from, tip.to, tip.value), (wallet.address(), Some(builder_address), U256::exp10(15)));
+    assert_eq!(signed.start_nonces, vec![(wallet.address(), U256::one())]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn first_per_gas_tip_from_its_own_wallet_pays_for_the_bundle_gas() -> anyhow::Result<()> {
+    let node = MockNode::start(56).await?;
+    let (provider, wallet) = funded(&node).await?;
+    let tipper = LocalWallet::new(&mut rand::thread_rng());
+    node.set_balance(tipper.address(), U256::exp10(18));
+
+    let mut builder = BundleBuilder::new(provider.clone());
+    builder.push(TransactionRequest::pay(Address::random(), 1_000u64).into(), wallet.clone());
+    builder.push(TransactionRequest::pay(Address::random(), 1_000u64).into(), wallet.clone());
+    builder.tip(BuilderTip {
+        to: Address::random(),
+        signer: tipper.clone().into(),
+        amount: TipAmount::PerGas(U256::from(10)),
+        placement: TipPlacement::First,
+    });
+
+    let signed = builder.build().await?;
+
+    let txs = signed.txs.iter().map(|tx| decode(tx)).collect::<anyhow::Result<Vec<_>>>()?;
+    assert_eq!(txs[0].from, tipper.address());
+    assert_eq!(txs[0].nonce, U256::zero());
+    // Two plain transfers, estimated at 21k gas each
+    assert_eq!(txs[0].value, U256::from(10 * 2 * 21_000));
+    assert_eq!(txs[1..].iter().map(|tx| tx.nonce.as_u64()).collect::<Vec<_>>(), vec![0, 1]);
+    Ok(())
+}
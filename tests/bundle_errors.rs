@@ -0,0 +1,71 @@
+//! How relay JSON-RPC errors map onto `BundleError`, one error shape per relay.
+
+use serde_json::json;
+use test_blox::bundle::*;
+
+fn rpc_error(relay: &str, status: u16, error: serde_json::Value) -> BundleError {
+    BundleError::from_rpc(relay, status, serde_json::from_value(error).unwrap())
+}
+
+#[test]
+fn bloxroute_bad_auth_header_is_invalid_auth() {
+    let err = rpc_error("bloxroute", 401, json!({ "code": -32600, "message": "Invalid authorization header" }));
+    assert!(matches!(err, BundleError::InvalidAuth { ref relay, .. } if relay == "bloxroute"));
+    assert!(!err.is_retryable());
+}
+
+#[test]
+fn auth_error_code_in_a_200_body_is_invalid_auth() {
+    let err = rpc_error("blockrazor", 200, json!({ "code": 403, "message": "unknown token" }));
+    assert!(matches!(err, BundleError::InvalidAuth { .. }));
+}
+
+#[test]
+fn auth_in_the_message_alone_is_not_invalid_auth() {
+    for message in ["unauthorized sender nonce", "author mismatch"] {
+        let err = rpc_error("48club", 200, json!({ "code": -32000, "message": message }));
+        assert!(matches!(err, BundleError::Rejected { code: -32000, .. }), "{}", message);
+    }
+}
+
+#[test]
+fn club48_block_too_low_is_stale_block() {
+    let err = rpc_error("48club", 200, json!({ "code": -32000, "message": "block number too low" }));
+    assert!(matches!(err, BundleError::StaleBlock { .. }));
+    assert!(err.is_retryable());
+}
+
+#[test]
+fn bep322_past_max_block_is_stale_block() {
+    let err = rpc_error("bep322", 200, json!({ "code": -38000, "message": "maxBlockNumber is in the past" }));
+    assert!(matches!(err, BundleError::StaleBlock { .. }));
+}
+
+#[test]
+fn http_429_is_rate_limited() {
+    let err = rpc_error("bloxroute", 429, json!({ "code": -32005, "message": "slow down" }));
+    assert!(matches!(err, BundleError::RateLimited { .. }));
+    assert!(err.is_retryable());
+
+    let err = rpc_error("48club", 200, json!({ "code": -32005, "message": "Too Many Requests" }));
+    assert!(matches!(err, BundleError::RateLimited { .. }));
+}
+
+#[test]
+fn anything_else_is_rejected_with_its_code() {
+    let err = rpc_error(
+        "blockrazor",
+        200,
+        json!({ "code": -38003, "message": "bundle simulation failed: execution reverted", "data": "0x" }),
+    );
+    assert!(matches!(err, BundleError::Rejected { code: -38003, .. }));
+    assert!(!err.is_retryable());
+}
+
+#[test]
+fn only_server_side_invalid_responses_are_retryable() {
+    let server = BundleError::InvalidResponse { relay: "bep322".into(), status: 502, body: "Bad Gateway".into() };
+    let client = BundleError::InvalidResponse { relay: "bep322".into(), status: 400, body: "nope".into() };
+    assert!(server.is_retryable());
+    assert!(!client.is_retryable());
+}
@@ -0,0 +1,115 @@
+//! Bundle inclusion tracking against an in-memory `MockNode`, mined by hand or by `MockRelay`
+
+use ethers::prelude::*;
+use futures::TryStreamExt;
+use std::{sync::Arc, time::Duration};
+use test_blox::bundle::*;
+
+struct Setup {
+    node: MockNode,
+    provider: Arc<Provider<Http>>,
+    wallet: LocalWallet,
+}
+
+/// A funded wallet on a node that only mines when asked
+async fn setup() -> anyhow::Result<Setup> {
+    let node = MockNode::start(56).await?;
+    node.set_automine(false);
+    let provider = Arc::new(Provider::<Http>::try_from(node.url())?.interval(Duration::from_millis(10)));
+    let wallet = LocalWallet::new(&mut rand::thread_rng());
+    node.set_balance(wallet.address(), U256::exp10(18));
+    Ok(Setup { node, provider, wallet })
+}
+
+fn transfer_bundle(setup: &Setup, recipients: &[Address]) -> BundleBuilder {
+    let mut builder = BundleBuilder::new(setup.provider.clone());
+    for to in recipients {
+        builder.push(TransactionRequest::pay(*to, 1_000u64).into(), setup.wallet.clone());
+    }
+    builder
+}
+
+async fn send_raw(provider: &Provider<Http>, raw_tx: &str) -> anyhow::Result<()> {
+    provider.send_raw_transaction(Bytes::from(hex::decode(raw_tx.trim_start_matches("0x"))?)).await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn bundle_mined_in_the_target_block_is_included() -> anyhow::Result<()> {
+    let setup = setup().await?;
+    let signed = transfer_bundle(&setup, &[Address::random(), Address::random()]).build().await?;
+    let tracker = signed.tracker(setup.provider.clone(), 1)?.poll_interval(Duration::from_millis(10));
+
+    for tx in &signed.txs {
+        send_raw(&setup.provider, tx).await?;
+    }
+    setup.node.mine();
+
+    assert_eq!(tracker.check_block(1).await?, BundleStatus::Included { block: 1 });
+    Ok(())
+}
+
+#[tokio::test]
+async fn bundle_with_a_missing_tx_is_partially_included() -> anyhow::Result<()> {
+    let setup = setup().await?;
+    let signed = transfer_bundle(&setup, &[Address::random(), Address::random()]).build().await?;
+    let tracker = signed.tracker(setup.provider.clone(), 1)?.poll_interval(Duration::from_millis(10));
+
+    send_raw(&setup.provider, &signed.txs[0]).await?;
+    setup.node.mine();
+
+    let missing = vec![signed_tx_hash(&signed.txs[1])?];
+    assert!(matches!(
+        tracker.check_block(1).await?,
+        BundleStatus::PartiallyIncluded { block: 1, missing: m, .. } if m == missing
+    ));
+    Ok(())
+}
+
+#[tokio::test]
+async fn reverted_tx_fails_the_bundle() -> anyhow::Result<()> {
+    let setup = setup().await?;
+    let target = Address::random();
+    setup.node.revert_calls_to(target);
+    let signed = transfer_bundle(&setup, &[target]).build().await?;
+    let tracker = signed.tracker(setup.provider.clone(), 1)?.poll_interval(Duration::from_millis(10));
+
+    send_raw(&setup.provider, &signed.txs[0]).await?;
+    setup.node.mine();
+
+    let tx_hash = signed_tx_hash(&signed.txs[0])?;
+    assert_eq!(tracker.check_block(1).await?, BundleStatus::Reverted { block: 1, tx_hash });
+    Ok(())
+}
+
+#[tokio::test]
+async fn stream_reports_every_empty_block_then_expires() -> anyhow::Result<()> {
+    let setup = setup().await?;
+    let signed = transfer_bundle(&setup, &[Address::random()]).build().await?;
+    let tracker = signed
+        .tracker(setup.provider.clone(), 1)?
+        .until_block(2)
+        .poll_interval(Duration::from_millis(10));
+
+    setup.node.mine();
+    setup.node.mine();
+    let statuses: Vec<BundleStatus> = tracker.into_stream().try_collect().await?;
+
+    assert_eq!(statuses, vec![BundleStatus::Pending { block: 1 }, BundleStatus::Expired { last_block: 2 }]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn bundle_sent_through_mock_relay_is_included_in_its_target_block() -> anyhow::Result<()> {
+    let setup = setup().await?;
+    let mock = MockRelay::start(&setup.node.url(), None).await?;
+    let relay = Bep322Relay::new(&mock.url(), None);
+
+    let builder = transfer_bundle(&setup, &[Address::random(), Address::random()]);
+    let signed = builder.build().await?;
+    let status = submit_bundle_window(&relay, &builder, signed, setup.provider.clone(), 1, None).await?;
+
+    assert_eq!(status, BundleStatus::Included { block: 1 });
+    assert_eq!(mock.received().len(), 1);
+    Ok(())
+}
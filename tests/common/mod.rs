@@ -0,0 +1,26 @@
+//! Helpers shared by the integration tests, not every test file uses all of them
+#![allow(dead_code)]
+
+use ethers::utils::{Anvil, AnvilInstance};
+use std::{process::Command, sync::Once};
+
+/// A fresh local anvil node, `None` (with a note) when `anvil` isn't on PATH so the
+/// calling test can return early instead of failing
+pub fn spawn_anvil() -> Option<AnvilInstance> {
+    if Command::new("anvil").arg("--version").output().is_err() {
+        println!("⏭️ anvil not found on PATH, skipping");
+        return None;
+    }
+    Some(Anvil::new().spawn())
+}
+
+/// Moves the test process into its own temp directory, once. Journals and wallet files
+/// live under `src/` relative to the working directory and must stay out of the repo.
+pub fn in_temp_dir() {
+    static ONCE: Once = Once::new();
+    ONCE.call_once(|| {
+        let dir = std::env::temp_dir().join(format!("test-blox-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::env::set_current_dir(&dir).unwrap();
+    });
+}
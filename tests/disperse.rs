@@ -0,0 +1,88 @@
+//! Batch funding through the bundled Disperse contract on a local anvil node.
+//! The anvil tests return early when `anvil` is not on PATH.
+
+use ethers::abi::AbiDecode;
+use ethers::prelude::*;
+use ethers::utils::AnvilInstance;
+use std::time::Duration;
+use test_blox::bundle::SendMode;
+use test_blox::funding::*;
+
+mod common;
+use common::spawn_anvil;
+
+fn treasury(anvil: &AnvilInstance) -> anyhow::Result<SignerMiddleware<Provider<Http>, LocalWallet>> {
+    let provider = Provider::<Http>::try_from(anvil.endpoint())?.interval(Duration::from_millis(50));
+    let wallet: LocalWallet = anvil.keys()[0].clone().into();
+    Ok(SignerMiddleware::new(provider, wallet.with_chain_id(anvil.chain_id())))
+}
+
+#[tokio::test]
+async fn disperse_funds_every_wallet_in_one_tx() -> anyhow::Result<()> {
+    let Some(anvil) = spawn_anvil() else { return Ok(()) };
+    let client = treasury(&anvil)?;
+    let disperse = deploy_disperse(&client).await?;
+
+    let transfers: Vec<Transfer> = (1..=5u64)
+        .map(|i| Transfer { to: Address::from_low_u64_be(0x1000 + i), amount: U256::exp10(15) * i })
+        .collect();
+    let summary = disperse_bnb(&client, disperse, &transfers, &SendMode::Public, Duration::from_secs(10)).await?;
+
+    summary.ensure_all_funded()?;
+    let hashes: Vec<_> = summary
+        .outcomes
+        .iter()
+        .map(|o| match o.status {
+            TransferStatus::Funded { tx_hash, .. } => tx_hash,
+            TransferStatus::Pending { .. } | TransferStatus::Failed { .. } => unreachable!(),
+        })
+        .collect();
+    assert!(hashes.iter().all(|h| *h == hashes[0]));
+    for t in &transfers {
+        assert_eq!(client.get_balance(t.to, None).await?, t.amount);
+    }
+    // Nothing stays behind in the contract
+    assert_eq!(client.get_balance(disperse, None).await?, U256::zero());
+    Ok(())
+}
+
+#[tokio::test]
+async fn disperse_refuses_address_without_code() -> anyhow::Result<()> {
+    let Some(anvil) = spawn_anvil() else { return Ok(()) };
+    let client = treasury(&anvil)?;
+    let transfers = [Transfer { to: anvil.addresses()[2], amount: U256::exp10(15) }];
+
+    let err = disperse_bnb(&client, anvil.addresses()[1], &transfers, &SendMode::Public, Duration::from_secs(10))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("No contract"));
+    Ok(())
+}
+
+#[test]
+fn disperse_call_value_is_the_sum_of_encoded_values() -> anyhow::Result<()> {
+    let transfers: Vec<Transfer> = (1..=3u64)
+        .map(|i| Transfer { to: Address::from_low_u64_be(0x1000 + i), amount: U256::exp10(15) * i })
+        .collect();
+    let tx = disperse_tx(Address::zero(), Address::from_low_u64_be(0xd15), &transfers)?;
+
+    let call = DisperseEtherCall::decode(tx.data.as_ref().expect("calldata"))?;
+    assert_eq!(call.recipients, transfers.iter().map(|t| t.to).collect::<Vec<_>>());
+    assert_eq!(call.values, transfers.iter().map(|t| t.amount).collect::<Vec<_>>());
+    assert_eq!(tx.value, Some(call.values.iter().fold(U256::zero(), |sum, v| sum + v)));
+
+    let overflow = [Transfer { to: Address::zero(), amount: U256::MAX }, Transfer { to: Address::zero(), amount: U256::one() }];
+    assert!(disperse_tx(Address::zero(), Address::zero(), &overflow).is_err());
+    Ok(())
+}
+
+#[test]
+fn bytecode_matches_its_listing() {
+    // Instruction lines: 4-digit offset, then the opcode and its immediate as hex in columns 6..18
+    let listing: String = include_str!("../src/funding/disperse.evm")
+        .lines()
+        .filter(|line| line.len() > 18 && line[..4].chars().all(|c| c.is_ascii_hexdigit()))
+        .flat_map(|line| line[6..18].split_whitespace())
+        .collect();
+    assert_eq!(format!("0x{}", listing), DISPERSE_BYTECODE);
+}
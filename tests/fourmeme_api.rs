@@ -0,0 +1,79 @@
+//! Login, session reuse and re-login of `FourMemeApi` against `MockFourMeme`
+
+use ethers::prelude::*;
+use std::path::PathBuf;
+use test_blox::fourmeme::*;
+use test_blox::wallet::WalletSigner;
+
+fn test_image() -> anyhow::Result<PathBuf> {
+    let path = std::env::temp_dir().join(format!("fourmeme-mock-{:x}.png", H64::random()));
+    std::fs::write(&path, b"\x89PNG\r\n\x1a\nnot really an image")?;
+    Ok(path)
+}
+
+fn create_request(img_url: String) -> CreateTokenRequest {
+    CreateTokenRequest {
+        name: "Mock".to_string(),
+        short_name: "MOCK".to_string(),
+        desc: "mock token".to_string(),
+        img_url,
+        launch_time: 0,
+        label: "Meme".to_string(),
+        lp_trading_fee: 0.0025,
+        web_url: String::new(),
+        twitter_url: String::new(),
+        telegram_url: String::new(),
+        pre_sale: "0".to_string(),
+        only_mpc: false,
+        raised_amount: 24,
+        symbol: "BNB".to_string(),
+    }
+}
+
+#[tokio::test]
+async fn session_is_reused_across_calls() -> anyhow::Result<()> {
+    let mock = MockFourMeme::start().await?;
+    let api = mock.api();
+    let wallet: WalletSigner = LocalWallet::new(&mut rand::thread_rng()).into();
+    let image = test_image()?;
+
+    let url = api.upload_image_as(&wallet, &image).await?;
+    let created = api.create_token_as(&wallet, &create_request(url)).await?;
+
+    assert_eq!(mock.logins(), 1);
+    assert_eq!(mock.uploads(), 1);
+    assert_eq!(created.create_arg, MOCK_CREATE_ARG.parse::<Bytes>()?);
+    assert_eq!(created.signature, MOCK_CREATE_SIGNATURE.parse::<Bytes>()?);
+    std::fs::remove_file(image)?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn rejected_session_logs_in_again_once() -> anyhow::Result<()> {
+    let mock = MockFourMeme::start().await?;
+    let api = mock.api();
+    let wallet: WalletSigner = LocalWallet::new(&mut rand::thread_rng()).into();
+    let image = test_image()?;
+
+    let url = api.upload_image_as(&wallet, &image).await?;
+    mock.expire_sessions();
+    api.create_token_as(&wallet, &create_request(url)).await?;
+
+    assert_eq!(mock.logins(), 2);
+    std::fs::remove_file(image)?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn stale_token_is_a_session_error() -> anyhow::Result<()> {
+    let mock = MockFourMeme::start().await?;
+    let api = mock.api();
+    let image = test_image()?;
+
+    let err = api.upload_image("not-a-session", &image).await.unwrap_err();
+
+    assert!(err.is::<SessionRejected>());
+    assert_eq!(mock.uploads(), 0);
+    std::fs::remove_file(image)?;
+    Ok(())
+}
@@ -0,0 +1,33 @@
+use ethers::prelude::*;
+use test_blox::funding::*;
+
+#[test]
+fn top_up_is_what_the_wallet_lacks() {
+    let target = U256::exp10(17);
+    assert_eq!(top_up_amount(target, U256::zero()), Some(target));
+    assert_eq!(top_up_amount(target, U256::exp10(16)), Some(target - U256::exp10(16)));
+    assert_eq!(top_up_amount(target, target), None);
+    assert_eq!(top_up_amount(target, target + 1), None);
+}
+
+#[tokio::test]
+async fn top_up_plan_skips_wallets_at_the_target() -> anyhow::Result<()> {
+    let (provider, mock) = Provider::mocked();
+    let target = U256::exp10(17);
+    let wallets: Vec<Address> = (1..=3u64).map(Address::from_low_u64_be).collect();
+    // The mock answers last-pushed first
+    for balance in [U256::exp10(16), target + 1, U256::zero()].iter().rev() {
+        mock.push(*balance)?;
+    }
+
+    let transfers = plan_transfers(&provider, &wallets, &FundingAmount::TopUp { target }).await?;
+
+    assert_eq!(
+        transfers,
+        vec![
+            Transfer { to: wallets[0], amount: target - U256::exp10(16) },
+            Transfer { to: wallets[2], amount: target },
+        ]
+    );
+    Ok(())
+}
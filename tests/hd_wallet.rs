@@ -0,0 +1,28 @@
+//! BIP-39/BIP-44 derivation against the well-known test mnemonic
+
+use ethers::prelude::*;
+use test_blox::types::*;
+use test_blox::wallet::*;
+
+const TEST_MNEMONIC: &str = "test test test test test test test test test test test junk";
+
+#[test]
+fn first_derived_wallet_matches_the_known_vector() -> anyhow::Result<()> {
+    let wallet = derive_wallet(TEST_MNEMONIC, 0)?;
+    let expected: Address = "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266".parse()?;
+    assert_eq!(wallet.address(), expected);
+    Ok(())
+}
+
+#[test]
+fn derived_range_keeps_its_indices() -> anyhow::Result<()> {
+    let wallets = derive_wallet_range(TEST_MNEMONIC, 1..3)?;
+    let expected: Vec<Address> = vec![
+        "0x70997970C51812dc3A010C7d01b50e0d17dc79C8".parse()?,
+        "0x3C44CdDdB6a900fa2b585dd299e03d12FA4293BC".parse()?,
+    ];
+    assert_eq!(wallets.iter().map(|w| w.address).collect::<Vec<_>>(), expected);
+    assert_eq!(wallets[0].key, KeySource::Derived { hd_index: 1 });
+    assert_eq!(next_hd_index(&wallets), 3);
+    Ok(())
+}
@@ -0,0 +1,56 @@
+//! Import → export → re-import of wallets, run in a temp working directory
+
+use ethers::prelude::*;
+use std::{fs, path::Path};
+use test_blox::wallet::*;
+
+mod common;
+use common::in_temp_dir;
+
+const PASSPHRASE: &str = "round trip";
+
+fn key_of(wallet: &LocalWallet) -> String {
+    format!("0x{}", hex::encode(wallet.signer().to_bytes()))
+}
+
+#[test]
+fn exported_wallets_import_back_as_duplicates() -> anyhow::Result<()> {
+    in_temp_dir();
+    let dir = Path::new("round-trip");
+    fs::create_dir_all(dir)?;
+    let (a, b) = (LocalWallet::new(&mut rand::thread_rng()), LocalWallet::new(&mut rand::thread_rng()));
+    let source = dir.join("source.csv");
+    fs::write(
+        &source,
+        format!(
+            "address,key,label\n{:?},{},alice\n{:?},{},bob\n{:?},{},alice again\n{:?},0x1234,broken\n",
+            a.address(),
+            key_of(&a),
+            b.address(),
+            key_of(&b),
+            a.address(),
+            key_of(&a),
+            Address::random(),
+        ),
+    )?;
+
+    let report = import_wallets_csv(&source, Some("round-trip"), PASSPHRASE, PASSPHRASE)?;
+    assert_eq!(report.imported.iter().map(|w| w.address).collect::<Vec<_>>(), vec![a.address(), b.address()]);
+    assert_eq!(report.imported[0].label.as_deref(), Some("alice"));
+    assert_eq!(report.duplicates, vec![a.address()]);
+    assert_eq!(report.rejected.len(), 1);
+
+    // Both export formats carry the same keys, so importing them again only finds duplicates
+    let csv = dir.join("export.csv");
+    export_wallets_csv(&csv, &report.imported, PASSPHRASE, CsvKeyColumn::PrivateKey)?;
+    let again = import_wallets_csv(&csv, None, PASSPHRASE, PASSPHRASE)?;
+    assert!(again.imported.is_empty() && again.rejected.is_empty(), "{:?}", again);
+    assert_eq!(again.duplicates, vec![a.address(), b.address()]);
+
+    let keystores = dir.join("keystores");
+    assert_eq!(export_keystore_dir(&keystores, &report.imported, PASSPHRASE)?, 2);
+    let again = import_keystore_dir(&keystores, None, PASSPHRASE, PASSPHRASE)?;
+    assert!(again.imported.is_empty() && again.rejected.is_empty(), "{:?}", again);
+    assert_eq!(again.duplicates.len(), 2);
+    Ok(())
+}
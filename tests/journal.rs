@@ -0,0 +1,69 @@
+use ethers::prelude::*;
+use std::time::Duration;
+use test_blox::bundle::{MockNode, NodeFailure, SendMode};
+use test_blox::funding::{FundingMode, JournalEntry, JournalStatus, RunJournal, RunKind, run_distribution};
+
+mod common;
+use common::in_temp_dir;
+
+#[test]
+fn pending_entries_from_before_nonce_tracking_still_load() {
+    let json = r#"{
+        "from": "0x0000000000000000000000000000000000000001",
+        "to": "0x0000000000000000000000000000000000000002",
+        "amount": "0x1",
+        "status": "pending",
+        "tx_hash": "0x0000000000000000000000000000000000000000000000000000000000000003"
+    }"#;
+    let entry: JournalEntry = serde_json::from_str(json).unwrap();
+    assert_eq!(
+        entry.status,
+        JournalStatus::Pending { tx_hash: H256::from_low_u64_be(3), nonce: None, raw: None }
+    );
+}
+
+#[test]
+fn pending_entries_keep_nonce_and_raw_tx() {
+    let status = JournalStatus::Pending {
+        tx_hash: H256::from_low_u64_be(3),
+        nonce: Some(U256::from(7)),
+        raw: Some(Bytes::from(vec![0x02, 0xf8])),
+    };
+    let json = serde_json::to_string(&status).unwrap();
+    assert_eq!(serde_json::from_str::<JournalStatus>(&json).unwrap(), status);
+}
+
+#[tokio::test]
+async fn resumed_run_rebroadcasts_a_failed_send_instead_of_paying_twice() -> anyhow::Result<()> {
+    in_temp_dir();
+    let node = MockNode::start(56).await?;
+    let provider = Provider::<Http>::try_from(node.url())?.interval(Duration::from_millis(10));
+    let treasury = LocalWallet::new(&mut rand::thread_rng()).with_chain_id(56u64);
+    node.set_balance(treasury.address(), U256::exp10(18));
+    let client = SignerMiddleware::new(provider, treasury.clone());
+
+    let wallets: Vec<Address> = (1..=3u64).map(Address::from_low_u64_be).collect();
+    let entries = wallets.iter().map(|to| JournalEntry::planned(treasury.address(), *to, U256::exp10(15))).collect();
+    let run_id = format!("resume-{}", H256::random());
+    let mut journal = RunJournal::create(&run_id, RunKind::Distribute, FundingMode::Transfers, None, entries)?;
+
+    // The first send errors without the node seeing it, e.g. a private relay that timed out
+    node.fail_next(NodeFailure::Reject("relay timed out".to_string()));
+    let timeout = Duration::from_millis(300);
+    run_distribution(&client, &mut journal, &SendMode::Public, timeout).await?;
+
+    let first = &journal.entries[0].status;
+    assert!(matches!(first, JournalStatus::Pending { nonce: Some(n), raw: Some(_), .. } if n.is_zero()), "{:?}", first);
+    // The rest kept their own nonces and wait behind it
+    assert!(node.mined().is_empty());
+
+    let mut journal = RunJournal::load(&run_id)?.expect("journal saved");
+    run_distribution(&client, &mut journal, &SendMode::Public, timeout).await?;
+
+    assert!(journal.is_complete(), "{:?}", journal.entries);
+    assert_eq!(node.mined().len(), 3);
+    for to in wallets {
+        assert_eq!(node.balance(to), U256::exp10(15));
+    }
+    Ok(())
+}
@@ -0,0 +1,32 @@
+//! Moving plaintext wallet files into keystores, run in a temp working directory
+
+use ethers::prelude::*;
+use std::{fs, path::Path};
+use test_blox::types::WalletInfo;
+use test_blox::wallet::*;
+
+mod common;
+use common::in_temp_dir;
+
+#[test]
+fn migrated_wallet_unlocks_to_the_same_key_only_with_its_passphrase() -> anyhow::Result<()> {
+    in_temp_dir();
+    let wallet = LocalWallet::new(&mut rand::thread_rng());
+    let key = format!("0x{}", hex::encode(wallet.signer().to_bytes()));
+    let path = Path::new(WALLETS_DIR).join(format!("plaintext-{:?}.json", wallet.address()));
+    fs::create_dir_all(WALLETS_DIR)?;
+    fs::write(&path, serde_json::json!([{ "address": wallet.address(), "private_key": key }]).to_string())?;
+
+    assert!(migrate_plaintext_wallets("correct horse")? >= 1);
+
+    let index = fs::read_to_string(&path)?;
+    assert!(!index.contains(key.trim_start_matches("0x")), "plaintext key left in {}", path.display());
+    let migrated: Vec<WalletInfo> = serde_json::from_str(&index)?;
+    assert_eq!(migrated.len(), 1);
+
+    let unlocked = unlock_wallet(&migrated[0], "correct horse")?;
+    assert_eq!(unlocked.address(), wallet.address());
+    assert_eq!(unlocked.signer().to_bytes(), wallet.signer().to_bytes());
+    assert!(unlock_wallet(&migrated[0], "wrong passphrase").is_err());
+    Ok(())
+}
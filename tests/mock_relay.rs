@@ -0,0 +1,126 @@
+//! Bundle submission against `MockRelay` on a local anvil node.
+//! Each test returns early when `anvil` is not on PATH.
+
+use ethers::prelude::*;
+use ethers::utils::{AnvilInstance, parse_ether};
+use std::{sync::Arc, time::Duration};
+use test_blox::bundle::*;
+use test_blox::fourmeme::{create_token_tx, get_buy_token_tx};
+
+mod common;
+use common::spawn_anvil;
+
+/// Runtime code that takes any call and any value (a lone STOP), standing in for TokenManager2
+const ACCEPT_ALL_CODE: &str = "0x00";
+
+/// Two transfers from the first dev account, like create + buy from one wallet
+fn transfer_bundle(anvil: &AnvilInstance, provider: Arc<Provider<Http>>) -> BundleBuilder {
+    let wallet: LocalWallet = anvil.keys()[0].clone().into();
+    let mut builder = BundleBuilder::new(provider);
+    builder.push(TransactionRequest::pay(anvil.addresses()[1], 1_000u64).into(), wallet.clone());
+    builder.push(TransactionRequest::pay(anvil.addresses()[2], 1_000u64).into(), wallet);
+    builder
+}
+
+fn provider_for(anvil: &AnvilInstance) -> anyhow::Result<Arc<Provider<Http>>> {
+    Ok(Arc::new(
+        Provider::<Http>::try_from(anvil.endpoint())?.interval(Duration::from_millis(50)),
+    ))
+}
+
+#[tokio::test]
+async fn blxr_bundle_lands_in_target_block() -> anyhow::Result<()> {
+    let Some(anvil) = spawn_anvil() else { return Ok(()) };
+    let provider = provider_for(&anvil)?;
+    let mock = MockRelay::start(&anvil.endpoint(), Some("secret".to_string())).await?;
+    let relay = BloxrouteRelay::new("secret").with_url(&mock.url());
+
+    let builder = transfer_bundle(&anvil, provider.clone());
+    let signed = builder.build().await?;
+    let target = provider.get_block_number().await?.as_u64() + 1;
+    let status = submit_bundle_window(&relay, &builder, signed, provider, 1, None).await?;
+
+    assert_eq!(status, BundleStatus::Included { block: target });
+    assert_eq!(mock.received().len(), 1);
+    Ok(())
+}
+
+#[tokio::test]
+async fn eth_send_bundle_resubmits_after_dropped_block() -> anyhow::Result<()> {
+    let Some(anvil) = spawn_anvil() else { return Ok(()) };
+    let provider = provider_for(&anvil)?;
+    let mock = MockRelay::start(&anvil.endpoint(), None).await?;
+    mock.fail_next(MockFailure::DropBundle);
+    let relay = Bep322Relay::new(&mock.url(), None);
+
+    let builder = transfer_bundle(&anvil, provider.clone());
+    let signed = builder.build().await?;
+    let status = submit_bundle_window(&relay, &builder, signed, provider, 3, None).await?;
+
+    assert!(matches!(status, BundleStatus::Included { .. }));
+    assert_eq!(mock.received().len(), 2);
+    Ok(())
+}
+
+#[tokio::test]
+async fn auth_error_is_matchable() -> anyhow::Result<()> {
+    let Some(anvil) = spawn_anvil() else { return Ok(()) };
+    let provider = provider_for(&anvil)?;
+    let mock = MockRelay::start(&anvil.endpoint(), Some("secret".to_string())).await?;
+    let relay = BloxrouteRelay::new("wrong").with_url(&mock.url());
+
+    let signed = transfer_bundle(&anvil, provider.clone()).build().await?;
+    let target = provider.get_block_number().await?.as_u64() + 1;
+    let err = relay.send_bundle(&signed.request(target)).await.unwrap_err();
+
+    assert!(matches!(err, BundleError::InvalidAuth { .. }));
+    Ok(())
+}
+
+#[tokio::test]
+async fn slow_relay_times_out_as_transport_error() -> anyhow::Result<()> {
+    let Some(anvil) = spawn_anvil() else { return Ok(()) };
+    let provider = provider_for(&anvil)?;
+    let mock = MockRelay::start(&anvil.endpoint(), None).await?;
+    mock.fail_next(MockFailure::Timeout(Duration::from_secs(2)));
+    let client = reqwest::Client::builder().timeout(Duration::from_millis(200)).build()?;
+    let relay = Bep322Relay::new(&mock.url(), None).with_client(client);
+
+    let signed = transfer_bundle(&anvil, provider.clone()).build().await?;
+    let target = provider.get_block_number().await?.as_u64() + 1;
+    let err = relay.send_bundle(&signed.request(target)).await.unwrap_err();
+
+    assert!(matches!(err, BundleError::Transport { .. }));
+    Ok(())
+}
+
+#[tokio::test]
+async fn create_and_buy_bundle_lands_in_target_block() -> anyhow::Result<()> {
+    let Some(anvil) = spawn_anvil() else { return Ok(()) };
+    let provider = provider_for(&anvil)?;
+    let token_manager = Address::random();
+    provider.request::<_, ()>("anvil_setCode", (token_manager, ACCEPT_ALL_CODE)).await?;
+    let mock = MockRelay::start(&anvil.endpoint(), None).await?;
+    let relay = Bep322Relay::new(&mock.url(), None);
+
+    // Same pair as the launch menu: createToken, then a buy of the new token from the same wallet
+    let wallet: LocalWallet = anvil.keys()[0].clone().into();
+    let pre_sale = parse_ether("0.1")?;
+    let create_arg = Bytes::from(vec![1u8; 64]);
+    let signature = Bytes::from(vec![2u8; 65]);
+    let create = create_token_tx(wallet.clone(), provider.clone(), token_manager, create_arg, signature, pre_sale)?;
+    let buy = get_buy_token_tx(wallet.clone(), provider.clone(), token_manager, Address::random(), 10.0, "0.2").await?;
+
+    let mut builder = BundleBuilder::new(provider.clone());
+    builder.push(create, wallet.clone());
+    builder.push(buy, wallet);
+    let signed = builder.build().await?;
+    let target = provider.get_block_number().await?.as_u64() + 1;
+    let status = submit_bundle_window(&relay, &builder, signed, provider.clone(), 3, None).await?;
+
+    assert_eq!(status, BundleStatus::Included { block: target });
+    // Pre-sale plus deploy fee from the create, max funds from the buy
+    let expected = pre_sale + parse_ether("0.01")? + parse_ether("0.2")?;
+    assert_eq!(provider.get_balance(token_manager, None).await?, expected);
+    Ok(())
+}
@@ -0,0 +1,38 @@
+//! Portfolio report from a canned Multicall3 `aggregate3` answer served by `MockNode`
+
+use ethers::abi::{Token, encode};
+use ethers::prelude::*;
+use std::{sync::Arc, time::Duration};
+use test_blox::bundle::MockNode;
+use test_blox::portfolio::portfolio_report;
+
+/// One `(bool success, bytes returnData)` entry of an `aggregate3` result
+fn call_result(success: bool, value: Option<u64>) -> Token {
+    let data = value.map(|v| encode(&[Token::Uint(v.into())])).unwrap_or_default();
+    Token::Tuple(vec![Token::Bool(success), Token::Bytes(data)])
+}
+
+#[tokio::test]
+async fn aggregate3_results_map_onto_wallets_and_tokens() -> anyhow::Result<()> {
+    let node = MockNode::start(56).await?;
+    let provider = Arc::new(Provider::<Http>::try_from(node.url())?.interval(Duration::from_millis(10)));
+    let multicall = Address::random();
+    // Per wallet: getEthBalance, then balanceOf for each token; the second wallet's balanceOf reverts
+    let results = vec![
+        call_result(true, Some(5)),
+        call_result(true, Some(7)),
+        call_result(true, Some(0)),
+        call_result(false, None),
+    ];
+    node.set_call_result(multicall, encode(&[Token::Array(results)]).into());
+
+    let wallets = vec![("main".to_string(), Address::random()), ("w1".to_string(), Address::random())];
+    let token = Address::random();
+    let report = portfolio_report(provider, Some(multicall), &wallets, &[token]).await?;
+
+    assert_eq!(report.tokens, vec![token]);
+    let balances: Vec<(U256, Vec<Option<U256>>)> = report.wallets.iter().map(|w| (w.bnb, w.tokens.clone())).collect();
+    assert_eq!(balances, vec![(5.into(), vec![Some(7.into())]), (0.into(), vec![None])]);
+    assert_eq!(report.total_bnb(), 5.into());
+    Ok(())
+}
@@ -0,0 +1,52 @@
+//! Private single-tx sends through `MockRelay` on an in-memory `MockNode`
+
+use ethers::prelude::*;
+use std::{sync::Arc, time::Duration};
+use test_blox::bundle::*;
+
+struct Setup {
+    node: MockNode,
+    relay: MockRelay,
+    client: SignerMiddleware<Arc<Provider<Http>>, LocalWallet>,
+}
+
+/// A funded wallet and a relay that answers 401 to the next private tx
+async fn failing_relay() -> anyhow::Result<Setup> {
+    let node = MockNode::start(56).await?;
+    let provider = Arc::new(Provider::<Http>::try_from(node.url())?.interval(Duration::from_millis(10)));
+    let wallet = LocalWallet::new(&mut rand::thread_rng()).with_chain_id(56u64);
+    node.set_balance(wallet.address(), U256::exp10(18));
+    let relay = MockRelay::start(&node.url(), None).await?;
+    relay.fail_next(MockFailure::AuthError);
+    Ok(Setup { node, relay, client: SignerMiddleware::new(provider, wallet) })
+}
+
+fn private_mode(setup: &Setup, fallback: bool) -> SendMode {
+    let fallback = fallback.then(|| PublicFallback { after: Duration::from_secs(30), provider: setup.client.inner().clone() });
+    SendMode::Private { relay: Arc::new(BuilderPrivateTx::new(&setup.relay.url(), None)), fallback }
+}
+
+#[tokio::test]
+async fn relay_error_goes_public_at_once_with_a_fallback() -> anyhow::Result<()> {
+    let setup = failing_relay().await?;
+    let mode = private_mode(&setup, true);
+
+    let tx_hash = send_tx(&setup.client, TransactionRequest::pay(Address::random(), 1_000u64), &mode).await?;
+
+    assert_eq!(setup.node.received().iter().map(|tx| tx.hash).collect::<Vec<_>>(), vec![tx_hash]);
+    setup.node.mine();
+    assert!(setup.client.get_transaction_receipt(tx_hash).await?.is_some());
+    Ok(())
+}
+
+#[tokio::test]
+async fn relay_error_without_a_fallback_is_returned() -> anyhow::Result<()> {
+    let setup = failing_relay().await?;
+    let mode = private_mode(&setup, false);
+
+    let sent = send_tx(&setup.client, TransactionRequest::pay(Address::random(), 1_000u64), &mode).await;
+
+    assert!(sent.is_err());
+    assert!(setup.node.received().is_empty());
+    Ok(())
+}
@@ -0,0 +1,78 @@
+//! `RemoteSigner` against `MockRemoteSigner`, signatures must match signing locally.
+
+use ethers::prelude::*;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use test_blox::wallet::*;
+
+fn dev_wallet() -> LocalWallet {
+    "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80"
+        .parse::<LocalWallet>()
+        .unwrap()
+        .with_chain_id(56u64)
+}
+
+fn transfer(from: Address) -> TransactionRequest {
+    TransactionRequest::pay(Address::random(), 1_000u64)
+        .from(from)
+        .nonce(7u64)
+        .gas(21_000u64)
+        .gas_price(1_000_000_000u64)
+}
+
+#[tokio::test]
+async fn remote_legacy_tx_matches_local() -> anyhow::Result<()> {
+    let local = dev_wallet();
+    let mock = MockRemoteSigner::start(local.clone()).await?;
+    let remote = WalletSigner::from(mock.signer()).with_chain_id(56u64);
+
+    let tx: TypedTransaction = transfer(local.address()).into();
+    let remote_sig = remote.sign_transaction(&tx).await?;
+    let local_sig = local.sign_transaction(&tx).await?;
+
+    assert_eq!(remote_sig, local_sig);
+    assert_eq!(remote.address(), local.address());
+    Ok(())
+}
+
+#[tokio::test]
+async fn remote_eip1559_tx_matches_local() -> anyhow::Result<()> {
+    let local = dev_wallet();
+    let mock = MockRemoteSigner::start(local.clone()).await?;
+    let remote = mock.signer().with_chain_id(56u64);
+
+    let tx: TypedTransaction = Eip1559TransactionRequest::new()
+        .from(local.address())
+        .to(Address::random())
+        .value(1_000u64)
+        .nonce(3u64)
+        .gas(21_000u64)
+        .max_fee_per_gas(3_000_000_000u64)
+        .max_priority_fee_per_gas(1_000_000_000u64)
+        .into();
+
+    assert_eq!(remote.sign_transaction(&tx).await?, local.sign_transaction(&tx).await?);
+    Ok(())
+}
+
+#[tokio::test]
+async fn remote_message_signature_matches_local() -> anyhow::Result<()> {
+    let local = dev_wallet();
+    let mock = MockRemoteSigner::start(local.clone()).await?;
+    let remote = WalletSigner::from(mock.signer());
+
+    let message = "You are sign in Meme 1234";
+    assert_eq!(remote.sign_message(message).await?, local.sign_message(message).await?);
+    Ok(())
+}
+
+#[tokio::test]
+async fn signature_from_another_key_is_rejected() -> anyhow::Result<()> {
+    let mock = MockRemoteSigner::start(dev_wallet()).await?;
+    // Claims an address the stand-in doesn't hold the key for
+    let claimed = Address::random();
+    let remote = RemoteSigner::new(&mock.url(), claimed, None);
+
+    let err = remote.sign_message("hello").await.unwrap_err();
+    assert!(matches!(err, SignerError::WrongSignature(address) if address == claimed));
+    Ok(())
+}
@@ -0,0 +1,52 @@
+//! Exact-fee sweep txs on a local anvil node.
+//! The anvil tests return early when `anvil` is not on PATH.
+
+use ethers::prelude::*;
+use std::time::Duration;
+use test_blox::funding::*;
+
+mod common;
+use common::spawn_anvil;
+
+/// Funds a fresh wallet, sweeps it back with `tx_type` and checks nothing is left behind
+async fn sweep_leaves_zero(tx_type: SweepTxType) -> anyhow::Result<()> {
+    let Some(anvil) = spawn_anvil() else { return Ok(()) };
+    let provider = Provider::<Http>::try_from(anvil.endpoint())?.interval(Duration::from_millis(50));
+    let chain_id = provider.get_chainid().await?.as_u64();
+    let treasury = SignerMiddleware::new(provider.clone(), LocalWallet::from(anvil.keys()[0].clone()).with_chain_id(chain_id));
+    let wallet = LocalWallet::new(&mut rand::thread_rng()).with_chain_id(chain_id);
+    let client = SignerMiddleware::new(provider.clone(), wallet.clone());
+
+    let funding = TransactionRequest::pay(wallet.address(), U256::exp10(16));
+    treasury.send_transaction(funding, None).await?.await?;
+
+    let balance = provider.get_balance(wallet.address(), None).await?;
+    let gas = SweepGas::fetch(&provider, tx_type, wallet.address(), treasury.address()).await?;
+    let tx = gas.sweep_tx(wallet.address(), treasury.address(), balance, chain_id).expect("balance covers the fee");
+    let receipt = client.send_transaction(tx, None).await?.await?.expect("sweep mined");
+
+    assert_eq!(receipt.status, Some(1u64.into()));
+    assert_eq!(provider.get_balance(wallet.address(), None).await?, U256::zero());
+    Ok(())
+}
+
+#[tokio::test]
+async fn legacy_sweep_empties_wallet() -> anyhow::Result<()> {
+    sweep_leaves_zero(SweepTxType::Legacy).await
+}
+
+#[tokio::test]
+async fn eip1559_sweep_empties_wallet() -> anyhow::Result<()> {
+    sweep_leaves_zero(SweepTxType::Eip1559).await
+}
+
+#[test]
+fn sweep_tx_needs_more_than_the_fee() {
+    let gas = SweepGas { tx_type: SweepTxType::Legacy, gas_limit: 21_000u64.into(), gas_price: 1_000_000_000u64.into() };
+    let (from, to) = (Address::from_low_u64_be(1), Address::from_low_u64_be(2));
+
+    assert!(gas.sweep_tx(from, to, gas.fee(), 56).is_none());
+    let tx = gas.sweep_tx(from, to, gas.fee() + 5, 56).unwrap();
+    assert_eq!(tx.value(), Some(&U256::from(5)));
+    assert_eq!(tx.chain_id(), Some(56u64.into()));
+}
@@ -0,0 +1,65 @@
+//! Pipelined treasury transfers against an in-memory `MockNode`
+
+use ethers::prelude::*;
+use std::time::Duration;
+use test_blox::bundle::{MockNode, NodeFailure, SendMode};
+use test_blox::funding::*;
+
+const RECEIPT_TIMEOUT: Duration = Duration::from_millis(300);
+
+async fn funded_treasury(node: &MockNode) -> anyhow::Result<SignerMiddleware<Provider<Http>, LocalWallet>> {
+    let provider = Provider::<Http>::try_from(node.url())?.interval(Duration::from_millis(10));
+    let treasury = LocalWallet::new(&mut rand::thread_rng()).with_chain_id(56u64);
+    node.set_balance(treasury.address(), U256::exp10(18));
+    Ok(SignerMiddleware::new(provider, treasury))
+}
+
+fn transfers(n: u64) -> Vec<Transfer> {
+    (1..=n).map(|i| Transfer { to: Address::from_low_u64_be(i), amount: U256::exp10(15) }).collect()
+}
+
+#[tokio::test]
+async fn transfers_take_consecutive_nonces_and_report_gas() -> anyhow::Result<()> {
+    let node = MockNode::start(56).await?;
+    let client = funded_treasury(&node).await?;
+
+    let summary = send_transfers(&client, &transfers(3), &SendMode::Public, RECEIPT_TIMEOUT).await?;
+
+    let nonces: Vec<U256> = node.mined().iter().map(|tx| tx.nonce).collect();
+    assert_eq!(nonces, vec![0.into(), 1.into(), 2.into()]);
+    assert_eq!(summary.funded(), 3);
+    assert_eq!(summary.total_gas(), U256::from(3 * TRANSFER_GAS) * U256::exp10(9));
+    summary.ensure_all_funded()?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn send_error_after_the_node_took_the_tx_never_reuses_its_nonce() -> anyhow::Result<()> {
+    let node = MockNode::start(56).await?;
+    let client = funded_treasury(&node).await?;
+    node.fail_next(NodeFailure::AcceptThenError("request timed out".to_string()));
+
+    let summary = send_transfers(&client, &transfers(3), &SendMode::Public, RECEIPT_TIMEOUT).await?;
+
+    // One tx per nonce, nothing sent twice
+    let nonces: Vec<U256> = node.received().iter().map(|tx| tx.nonce).collect();
+    assert_eq!(nonces, vec![0.into(), 1.into(), 2.into()]);
+    assert_eq!(summary.funded(), 3);
+    Ok(())
+}
+
+#[tokio::test]
+async fn rejected_send_leaves_the_batch_pending_not_failed() -> anyhow::Result<()> {
+    let node = MockNode::start(56).await?;
+    let client = funded_treasury(&node).await?;
+    node.fail_next(NodeFailure::Reject("relay unavailable".to_string()));
+
+    let summary = send_transfers(&client, &transfers(2), &SendMode::Public, RECEIPT_TIMEOUT).await?;
+
+    // The second transfer keeps nonce 1 and waits behind the lost one
+    let nonces: Vec<U256> = node.received().iter().map(|tx| tx.nonce).collect();
+    assert_eq!(nonces, vec![1.into()]);
+    assert!(summary.outcomes.iter().all(|o| matches!(o.status, TransferStatus::Pending { .. })));
+    assert!(summary.ensure_all_funded().is_err());
+    Ok(())
+}